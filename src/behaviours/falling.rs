@@ -0,0 +1,38 @@
+use cgmath::Vector3;
+use specs::Builder;
+
+use crate::block_tick::{BlockBehaviour, TickContext};
+use crate::blocks::Block;
+use crate::components::{BoundingBox, FallingBlock, Position, Velocity};
+
+/// blocks like sand turn into a falling entity when there is nothing below them
+pub struct FallingBehaviour;
+
+impl BlockBehaviour for FallingBehaviour {
+    fn tick_delay(&self) -> u64 {
+        2
+    }
+    fn scheduled_tick(&self, ctx: &mut TickContext<'_, '_>, coords: Vector3<i32>) {
+        let block = match ctx.get_block(coords) {
+            Some(block) => block,
+            None => return,
+        };
+        let below = coords - Vector3::unit_y();
+        // an unloaded chunk below counts as support, the block would otherwise fall into nothing
+        if !block.falls() || !matches!(ctx.get_block(below), Some(b) if b.is_replaceable()) {
+            return;
+        }
+        ctx.set_block(coords, Block::Empty);
+        ctx.lazy
+            .create_entity(ctx.entities)
+            .with(FallingBlock(block))
+            .with(BoundingBox::unit_cube())
+            .with(Position(Vector3::new(
+                coords.x as f32,
+                coords.y as f32,
+                coords.z as f32,
+            )))
+            .with(Velocity(Vector3::new(0.0, 0.0, 0.0)))
+            .build();
+    }
+}
@@ -0,0 +1,33 @@
+use cgmath::Vector3;
+use rand::Rng;
+
+use crate::block_tick::{BlockBehaviour, TickContext};
+use crate::blocks::Block;
+
+/// grass dies when covered and spreads onto uncovered dirt next to it
+pub struct GrassBehaviour;
+
+impl BlockBehaviour for GrassBehaviour {
+    fn random_tick(&self, ctx: &mut TickContext<'_, '_>, coords: Vector3<i32>) {
+        let above = coords + Vector3::unit_y();
+        if !matches!(
+            ctx.get_block(above),
+            Some(Block::Empty) | Some(Block::TallGrass) | None
+        ) {
+            ctx.set_block(coords, Block::Dirt);
+            return;
+        }
+        let mut rng = rand::thread_rng();
+        let target = coords
+            + Vector3::new(
+                rng.gen_range(-1, 2),
+                rng.gen_range(-1, 2),
+                rng.gen_range(-1, 2),
+            );
+        if ctx.get_block(target) == Some(Block::Dirt)
+            && ctx.get_block(target + Vector3::unit_y()) == Some(Block::Empty)
+        {
+            ctx.set_block(target, Block::Grass);
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod falling;
+mod grass;
+mod water;
+
+pub use falling::FallingBehaviour;
+pub use grass::GrassBehaviour;
+pub use water::WaterBehaviour;
@@ -0,0 +1,33 @@
+use cgmath::Vector3;
+
+use crate::block_tick::{BlockBehaviour, TickContext};
+use crate::blocks::Block;
+use crate::dir::Dir;
+
+/// water flows down and over edges, it doesn't spread out on flat ground
+pub struct WaterBehaviour;
+
+impl BlockBehaviour for WaterBehaviour {
+    fn tick_delay(&self) -> u64 {
+        5
+    }
+    fn scheduled_tick(&self, ctx: &mut TickContext<'_, '_>, coords: Vector3<i32>) {
+        let below = coords - Vector3::unit_y();
+        match ctx.get_block(below) {
+            Some(Block::Empty) => {
+                ctx.set_block(below, Block::Water);
+            }
+            Some(Block::Water) | None => {}
+            Some(_) => {
+                for dir in [Dir::North, Dir::South, Dir::East, Dir::West].iter() {
+                    let side = coords + Vector3::from(*dir);
+                    if ctx.get_block(side) == Some(Block::Empty)
+                        && ctx.get_block(side - Vector3::unit_y()) == Some(Block::Empty)
+                    {
+                        ctx.set_block(side, Block::Water);
+                    }
+                }
+            }
+        }
+    }
+}
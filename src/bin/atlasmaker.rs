@@ -18,10 +18,28 @@ struct UvRect {
     down_right_y: f32,
 }
 
+/// textures that are taller than wide are vertical strips of square frames,
+/// the seconds every frame is shown are read from a json file next to the texture
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Animation {
+    frames: u32,
+    frame_time: f32,
+}
+
+#[derive(Deserialize)]
+struct AnimationMeta {
+    frame_time: f32,
+}
+
+const DEFAULT_FRAME_TIME: f32 = 0.25;
+
 #[derive(Serialize, Deserialize, Debug)]
 struct Texture {
     name: String,
+    /// the first frame of animated textures
     rect: UvRect,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    animation: Option<Animation>,
 }
 
 fn main() {
@@ -30,20 +48,47 @@ fn main() {
     let _ = args.next();
     let dst_path = args.next().unwrap();
 
-    let mut textures: Vec<(String, DynamicImage)> = args
+    let mut textures: Vec<(String, DynamicImage, Option<Animation>)> = args
         .into_iter()
         .map(|path| {
             let mut path_buf = PathBuf::from(&path);
             path_buf.set_extension("");
             let name: String = String::from(path_buf.file_name().unwrap().to_str().unwrap());
-            (name, image::open(path).unwrap())
+            let texture = image::open(&path).unwrap();
+            let frames = texture.height() / texture.width();
+            let animation = if frames > 1 && texture.height() % texture.width() == 0 {
+                path_buf.set_extension("json");
+                match std::fs::read_to_string(&path_buf) {
+                    Ok(meta) => match serde_json::from_str::<AnimationMeta>(&meta) {
+                        Ok(meta) => Some(Animation {
+                            frames,
+                            frame_time: meta.frame_time,
+                        }),
+                        Err(error) => {
+                            eprintln!(
+                                "{}: {}, the texture is not animated",
+                                path_buf.display(),
+                                error
+                            );
+                            None
+                        }
+                    },
+                    Err(_) => Some(Animation {
+                        frames,
+                        frame_time: DEFAULT_FRAME_TIME,
+                    }),
+                }
+            } else {
+                None
+            };
+            (name, texture, animation)
         })
         .collect();
 
     let mut height = 0;
     let mut width = 0;
 
-    for (_, texture) in &textures {
+    for (_, texture, _) in &textures {
         width = width.max(texture.width());
         height += texture.height();
     }
@@ -53,7 +98,7 @@ fn main() {
     let mut atlas_map = Vec::new();
 
     let mut y = 0;
-    for (name, texture) in &mut textures {
+    for (name, texture, animation) in &mut textures {
         let src_rect = Rect {
             top_left_y: 0,
             top_left_x: 0,
@@ -67,14 +112,19 @@ fn main() {
             down_right_y: y + texture.height(),
         };
         copy_image_to_image(texture, &mut atlas, src_rect, dst_rect);
+        let frame_height = match animation {
+            Some(animation) => texture.height() / animation.frames,
+            None => texture.height(),
+        };
         atlas_map.push(Texture {
             name: name.clone(),
             rect: UvRect {
                 top_left_x: dst_rect.top_left_x as f32 / width as f32,
                 top_left_y: dst_rect.top_left_y as f32 / height as f32,
                 down_right_x: dst_rect.down_right_x as f32 / width as f32,
-                down_right_y: dst_rect.down_right_y as f32 / height as f32,
+                down_right_y: (dst_rect.top_left_y + frame_height) as f32 / height as f32,
             },
+            animation: *animation,
         });
         y += texture.height();
     }
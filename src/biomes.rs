@@ -0,0 +1,75 @@
+use crate::blocks::Block;
+use crate::climate::Climate;
+use crate::structures::Structure;
+
+/// the kind of landscape a column belongs to, chosen by its climate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Plains,
+    Desert,
+    Ocean,
+    Mountains,
+}
+
+impl Biome {
+    pub fn from_climate(climate: Climate) -> Self {
+        if climate.humidity > 0.62 {
+            Biome::Ocean
+        } else if climate.temperature < 0.38 {
+            Biome::Mountains
+        } else if climate.temperature > 0.58 && climate.humidity < 0.45 {
+            Biome::Desert
+        } else {
+            Biome::Plains
+        }
+    }
+    /// the block at the top of the ground
+    pub fn surface(self) -> Block {
+        match self {
+            Biome::Plains => Block::Grass,
+            Biome::Desert | Biome::Ocean => Block::Sand,
+            Biome::Mountains => Block::Stone,
+        }
+    }
+    /// the blocks between the surface and the stone
+    pub fn filler(self) -> Block {
+        match self {
+            Biome::Plains => Block::Dirt,
+            Biome::Desert | Biome::Ocean => Block::Sand,
+            Biome::Mountains => Block::Stone,
+        }
+    }
+    /// the height of the ground in the middle of the biome and how far the hills go up and down
+    pub fn height(self) -> (f32, f32) {
+        match self {
+            Biome::Plains => (15.0, 3.0),
+            Biome::Desert => (14.0, 2.0),
+            Biome::Ocean => (6.0, 2.0),
+            Biome::Mountains => (24.0, 16.0),
+        }
+    }
+    /// the block that grows on top of the surface and how many of the columns get one
+    pub fn decoration(self) -> Option<(Block, f32)> {
+        match self {
+            Biome::Plains => Some((Block::TallGrass, 0.12)),
+            Biome::Desert | Biome::Ocean | Biome::Mountains => None,
+        }
+    }
+    /// the structures standing on the ground and how many of the columns get one
+    pub fn structures(self) -> &'static [(Structure, f32)] {
+        match self {
+            Biome::Plains => &[
+                (Structure::Tree, 0.008),
+                (Structure::Boulder, 0.0005),
+                (Structure::Ruin, 0.0001),
+            ],
+            Biome::Mountains => &[(Structure::Boulder, 0.003), (Structure::Tree, 0.002)],
+            Biome::Desert | Biome::Ocean => &[],
+        }
+    }
+}
+
+/// the biome of the column at `x` `z` in world coordinates
+pub fn biome_at(seed: u64, x: i32, z: i32) -> Biome {
+    Biome::from_climate(Climate::at(seed, x, z))
+}
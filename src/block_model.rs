@@ -0,0 +1,405 @@
+use cgmath::{InnerSpace, Vector3};
+
+use crate::{blocks::Orientation, dir::Dir};
+
+/// the two triangles of a face of the unit cube with the uv corner of every vertex,
+/// corner 0 is the top left of the texture, 1 top right, 2 bottom left and 3 bottom right
+#[rustfmt::skip]
+pub fn cube_face(dir: Dir) -> [([u8; 3], usize); 6] {
+    match dir {
+        Dir::North => [
+            ([0, 0, 1], 2), ([1, 0, 1], 3), ([1, 1, 1], 1),
+            ([0, 0, 1], 2), ([1, 1, 1], 1), ([0, 1, 1], 0),
+        ],
+        Dir::South => [
+            ([0, 0, 0], 2), ([1, 1, 0], 1), ([1, 0, 0], 3),
+            ([0, 0, 0], 2), ([0, 1, 0], 0), ([1, 1, 0], 1),
+        ],
+        Dir::East => [
+            ([1, 0, 0], 2), ([1, 1, 1], 1), ([1, 0, 1], 3),
+            ([1, 0, 0], 2), ([1, 1, 0], 0), ([1, 1, 1], 1),
+        ],
+        Dir::West => [
+            ([0, 0, 0], 2), ([0, 0, 1], 3), ([0, 1, 1], 1),
+            ([0, 0, 0], 2), ([0, 1, 1], 1), ([0, 1, 0], 0),
+        ],
+        Dir::Up => [
+            ([0, 1, 0], 0), ([1, 1, 1], 3), ([1, 1, 0], 2),
+            ([0, 1, 0], 0), ([0, 1, 1], 1), ([1, 1, 1], 3),
+        ],
+        Dir::Down => [
+            ([0, 0, 0], 0), ([1, 0, 0], 2), ([1, 0, 1], 3),
+            ([0, 0, 0], 0), ([1, 0, 1], 3), ([0, 0, 1], 1),
+        ],
+    }
+}
+
+/// a rotation by multiples of 90 degrees, given by where it turns the x, y and z axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockRotation {
+    pub x: Vector3<i32>,
+    pub y: Vector3<i32>,
+    pub z: Vector3<i32>,
+}
+
+impl BlockRotation {
+    pub fn apply(&self, v: Vector3<i32>) -> Vector3<i32> {
+        self.x * v.x + self.y * v.y + self.z * v.z
+    }
+    /// rotations are orthogonal, so the inverse is the transpose
+    pub fn apply_inverse(&self, v: Vector3<i32>) -> Vector3<i32> {
+        Vector3::new(self.x.dot(v), self.y.dot(v), self.z.dot(v))
+    }
+}
+
+/// rotates a block from its own space, where it faces up (or north for horizontal blocks),
+/// into the world, so that it faces `facing`
+#[rustfmt::skip]
+pub fn rotation(orientation: Orientation, facing: Dir) -> BlockRotation {
+    let x = |x, y, z| Vector3::new(x, y, z);
+    // the images of the x, y and z axis
+    let (ex, ey, ez) = match (orientation, facing) {
+        (Orientation::Axis, Dir::Down)        => (x(1, 0, 0),  x(0, -1, 0), x(0, 0, -1)),
+        (Orientation::Axis, Dir::North)       => (x(1, 0, 0),  x(0, 0, 1),  x(0, -1, 0)),
+        (Orientation::Axis, Dir::South)       => (x(1, 0, 0),  x(0, 0, -1), x(0, 1, 0)),
+        (Orientation::Axis, Dir::East)        => (x(0, -1, 0), x(1, 0, 0),  x(0, 0, 1)),
+        (Orientation::Axis, Dir::West)        => (x(0, 1, 0),  x(-1, 0, 0), x(0, 0, 1)),
+        (Orientation::Horizontal, Dir::South) => (x(-1, 0, 0), x(0, 1, 0),  x(0, 0, -1)),
+        (Orientation::Horizontal, Dir::East)  => (x(0, 0, -1), x(0, 1, 0),  x(1, 0, 0)),
+        (Orientation::Horizontal, Dir::West)  => (x(0, 0, 1),  x(0, 1, 0),  x(-1, 0, 0)),
+        _                                     => (x(1, 0, 0),  x(0, 1, 0),  x(0, 0, 1)),
+    };
+    BlockRotation { x: ex, y: ey, z: ez }
+}
+
+/// the shape of the model of a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Cube,
+    /// the lower half of a cube
+    Slab,
+    /// a slab with a step on its back (south) half, so the stairs go up away from the front
+    Stairs,
+    /// two crossing quads through the diagonals of the block, like plants
+    Cross,
+}
+
+/// a box of a model from its lowest to its highest corner, in half blocks
+type HalfBox = ([u8; 3], [u8; 3]);
+
+fn boxes(shape: Shape) -> &'static [HalfBox] {
+    match shape {
+        Shape::Cube => &[([0, 0, 0], [2, 2, 2])],
+        Shape::Slab => &[([0, 0, 0], [2, 1, 2])],
+        Shape::Stairs => &[([0, 0, 0], [2, 1, 2]), ([0, 1, 0], [2, 2, 1])],
+        Shape::Cross => &[],
+    }
+}
+
+/// a face of a block model turned into the world
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelFace {
+    /// where the face points in the world
+    pub normal: Dir,
+    /// the side of the unturned block the face belongs to, which picks its texture
+    pub side: Dir,
+    /// the neighbour that hides the face if it covers its whole side towards this block
+    pub cull: Option<Dir>,
+    /// two triangles with positions in half blocks and uvs on the 3x3 grid of the texture
+    pub vertices: [(Vector3<u8>, [usize; 2]); 6],
+}
+
+/// the axis a direction goes along, 0 for x, 1 for y and 2 for z, and whether it is positive
+fn axis(dir: Dir) -> (usize, bool) {
+    match dir {
+        Dir::East => (0, true),
+        Dir::West => (0, false),
+        Dir::Up => (1, true),
+        Dir::Down => (1, false),
+        Dir::North => (2, true),
+        Dir::South => (2, false),
+    }
+}
+
+/// whether the box `a` covers the whole face of `b` towards `dir`
+fn covers(a: &HalfBox, b: &HalfBox, dir: Dir) -> bool {
+    let (axis, positive) = axis(dir);
+    let touches = if positive {
+        a.0[axis] == b.1[axis]
+    } else {
+        a.1[axis] == b.0[axis]
+    };
+    touches
+        && (0..3)
+            .filter(|other| *other != axis)
+            .all(|other| a.0[other] <= b.0[other] && b.1[other] <= a.1[other])
+}
+
+/// the uv of a point on a face of the unturned block,
+/// so the texture is cut the same way as the face is
+fn face_uv(dir: Dir, position: [u8; 3]) -> [usize; 2] {
+    let [x, y, z] = [
+        position[0] as usize,
+        position[1] as usize,
+        position[2] as usize,
+    ];
+    match dir {
+        Dir::North | Dir::South => [x, 2 - y],
+        Dir::East | Dir::West => [z, 2 - y],
+        Dir::Up | Dir::Down => [z, x],
+    }
+}
+
+/// rotates a position in half blocks around the center of the block
+fn turn(rotation: &BlockRotation, position: Vector3<u8>) -> Vector3<u8> {
+    let centered = position.cast::<i32>().unwrap() - Vector3::new(1, 1, 1);
+    (rotation.apply(centered) + Vector3::new(1, 1, 1))
+        .cast::<u8>()
+        .unwrap()
+}
+
+/// the faces of a block model, turned so that it faces `facing`,
+/// faces that lie against another box of the same model are left out
+pub fn model_faces(shape: Shape, orientation: Orientation, facing: Dir) -> Vec<ModelFace> {
+    let rotation = rotation(orientation, facing);
+    let turn_dir = |dir: Dir| Dir::from_vector(rotation.apply(Vector3::from(dir))).unwrap();
+    let mut faces = vec![];
+    if shape == Shape::Cross {
+        #[rustfmt::skip]
+        let quads = [
+            [[0, 0, 0], [2, 0, 2], [2, 2, 2], [0, 2, 0]],
+            [[2, 0, 0], [0, 0, 2], [0, 2, 2], [2, 2, 0]],
+        ];
+        let uvs = [[0, 2], [2, 2], [2, 0], [0, 0]];
+        for quad in quads.iter() {
+            // both sides of the quad are visible
+            for order in [[0, 1, 2, 0, 2, 3], [0, 2, 1, 0, 3, 2]].iter() {
+                let mut vertices = [(Vector3::new(0, 0, 0), [0, 0]); 6];
+                for (vertex, i) in vertices.iter_mut().zip(order.iter()) {
+                    *vertex = (turn(&rotation, Vector3::from(quad[*i])), uvs[*i]);
+                }
+                faces.push(ModelFace {
+                    normal: Dir::Up,
+                    side: Dir::North,
+                    cull: None,
+                    vertices,
+                });
+            }
+        }
+        return faces;
+    }
+    let boxes = boxes(shape);
+    for (i, half_box) in boxes.iter().enumerate() {
+        for dir in Dir::iter() {
+            let hidden = boxes
+                .iter()
+                .enumerate()
+                .any(|(j, other)| i != j && covers(other, half_box, dir));
+            if hidden {
+                continue;
+            }
+            let (axis, positive) = axis(dir);
+            let on_side = if positive {
+                half_box.1[axis] == 2
+            } else {
+                half_box.0[axis] == 0
+            };
+            let mut vertices = [(Vector3::new(0, 0, 0), [0, 0]); 6];
+            for (vertex, (corner, _)) in vertices.iter_mut().zip(cube_face(dir).iter()) {
+                let mut position = [0; 3];
+                for (a, p) in position.iter_mut().enumerate() {
+                    *p = if corner[a] == 0 {
+                        half_box.0[a]
+                    } else {
+                        half_box.1[a]
+                    };
+                }
+                *vertex = (
+                    turn(&rotation, Vector3::from(position)),
+                    face_uv(dir, position),
+                );
+            }
+            faces.push(ModelFace {
+                normal: turn_dir(dir),
+                side: dir,
+                cull: if on_side { Some(turn_dir(dir)) } else { None },
+                vertices,
+            });
+        }
+    }
+    faces
+}
+
+/// whether a block model covers its whole side towards `world_dir`,
+/// so the face of the neighbour on that side can't be seen
+pub fn covers_side(shape: Shape, orientation: Orientation, facing: Dir, world_dir: Dir) -> bool {
+    let rotation = rotation(orientation, facing);
+    let dir = Dir::from_vector(rotation.apply_inverse(Vector3::from(world_dir))).unwrap();
+    let (axis, positive) = axis(dir);
+    let side = if positive { 2 } else { 0 };
+    let others = [(axis + 1) % 3, (axis + 2) % 3];
+    // every quarter of the side has to be covered by a box
+    (0..2).all(|a| {
+        (0..2).all(|b| {
+            boxes(shape).iter().any(|half_box| {
+                let touches = if positive {
+                    half_box.1[axis] == side
+                } else {
+                    half_box.0[axis] == side
+                };
+                touches
+                    && half_box.0[others[0]] <= a
+                    && a < half_box.1[others[0]]
+                    && half_box.0[others[1]] <= b
+                    && b < half_box.1[others[1]]
+            })
+        })
+    })
+}
+
+/// the boxes of a block that rays hit and entities bump into, in blocks
+pub fn collision_boxes(
+    shape: Shape,
+    orientation: Orientation,
+    facing: Dir,
+) -> Vec<(Vector3<f32>, Vector3<f32>)> {
+    if shape == Shape::Cross {
+        // a bit smaller than the block, plants are thin
+        return vec![(
+            Vector3::new(0.125, 0.0, 0.125),
+            Vector3::new(0.875, 0.75, 0.875),
+        )];
+    }
+    let rotation = rotation(orientation, facing);
+    boxes(shape)
+        .iter()
+        .map(|(min, max)| {
+            let a = turn(&rotation, Vector3::from(*min)).cast::<f32>().unwrap() / 2.0;
+            let b = turn(&rotation, Vector3::from(*max)).cast::<f32>().unwrap() / 2.0;
+            (
+                Vector3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+                Vector3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn rotations_face_the_right_way() {
+    for facing in Dir::iter() {
+        let axis = rotation(Orientation::Axis, facing).apply(Vector3::unit_y());
+        assert_eq!(axis, Vector3::from(facing));
+    }
+    for facing in [Dir::North, Dir::South, Dir::East, Dir::West].iter() {
+        let front = rotation(Orientation::Horizontal, *facing).apply(Vector3::unit_z());
+        assert_eq!(front, Vector3::from(*facing));
+        // horizontal blocks are never tilted
+        let up = rotation(Orientation::Horizontal, *facing).apply(Vector3::unit_y());
+        assert_eq!(up, Vector3::unit_y());
+    }
+}
+#[test]
+fn fixed_cubes_match_the_unit_cube() {
+    let faces = model_faces(Shape::Cube, Orientation::Fixed, Dir::East);
+    assert_eq!(faces.len(), 6);
+    for (face, dir) in faces.iter().zip(Dir::iter()) {
+        assert_eq!((face.normal, face.side, face.cull), (dir, dir, Some(dir)));
+        for ((position, uv), (expected, corner)) in face.vertices.iter().zip(cube_face(dir).iter())
+        {
+            assert_eq!(*position, Vector3::from(*expected) * 2);
+            assert_eq!(*uv, [corner % 2 * 2, corner / 2 * 2]);
+        }
+    }
+}
+#[test]
+fn turned_faces_stay_on_their_side() {
+    for orientation in [Orientation::Axis, Orientation::Horizontal].iter() {
+        for facing in Dir::iter() {
+            for face in model_faces(Shape::Cube, *orientation, facing) {
+                assert_eq!(face.cull, Some(face.normal));
+                let normal = Vector3::from(face.normal);
+                // all vertices lie on the plane of the face
+                for (position, _) in face.vertices.iter() {
+                    let p = position.cast::<i32>().unwrap();
+                    let on_face = |p: i32, n: i32| n == 0 || p == n + 1;
+                    assert!(
+                        on_face(p.x, normal.x) && on_face(p.y, normal.y) && on_face(p.z, normal.z)
+                    );
+                }
+            }
+        }
+    }
+    // the end of a log lying east west is on its east and west side
+    let side = |world_dir| {
+        model_faces(Shape::Cube, Orientation::Axis, Dir::East)
+            .iter()
+            .find(|face| face.normal == world_dir)
+            .unwrap()
+            .side
+    };
+    assert_eq!(side(Dir::East), Dir::Up);
+    assert_eq!(side(Dir::West), Dir::Down);
+    assert_eq!(side(Dir::Up), Dir::West);
+}
+#[test]
+fn slabs_are_half_a_cube() {
+    let faces = model_faces(Shape::Slab, Orientation::Fixed, Dir::Up);
+    assert_eq!(faces.len(), 6);
+    for face in faces.iter() {
+        assert!(face.vertices.iter().all(|(position, _)| position.y <= 1));
+        // the top of a slab is in the middle of the block, so nothing can hide it
+        match face.normal {
+            Dir::Up => assert_eq!(face.cull, None),
+            dir => assert_eq!(face.cull, Some(dir)),
+        }
+        // the sides only show the lower half of the texture
+        if face.normal != Dir::Up && face.normal != Dir::Down {
+            assert!(face.vertices.iter().all(|(_, uv)| uv[1] >= 1));
+        }
+    }
+    let covers = |dir| covers_side(Shape::Slab, Orientation::Fixed, Dir::Up, dir);
+    assert!(covers(Dir::Down));
+    assert!(!covers(Dir::Up));
+    assert!(!covers(Dir::North));
+}
+#[test]
+fn stairs_have_a_step_at_the_back() {
+    let faces = model_faces(Shape::Stairs, Orientation::Horizontal, Dir::North);
+    // the bottom of the step lies on the slab
+    assert_eq!(faces.len(), 11);
+    assert_eq!(faces.iter().filter(|face| face.cull.is_none()).count(), 2);
+    let covers = |facing, dir| covers_side(Shape::Stairs, Orientation::Horizontal, facing, dir);
+    assert!(covers(Dir::North, Dir::South));
+    assert!(covers(Dir::North, Dir::Down));
+    assert!(!covers(Dir::North, Dir::North));
+    assert!(!covers(Dir::North, Dir::Up));
+    assert!(!covers(Dir::North, Dir::East));
+    // turned around, the back is on the other side
+    assert!(covers(Dir::South, Dir::North));
+    assert!(!covers(Dir::South, Dir::South));
+
+    let boxes = collision_boxes(Shape::Stairs, Orientation::Horizontal, Dir::East);
+    assert_eq!(
+        boxes,
+        vec![
+            (Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.5, 1.0)),
+            (Vector3::new(0.0, 0.5, 0.0), Vector3::new(0.5, 1.0, 1.0)),
+        ]
+    );
+}
+#[test]
+fn crosses_are_never_culled() {
+    let faces = model_faces(Shape::Cross, Orientation::Fixed, Dir::Up);
+    // two quads, seen from both sides
+    assert_eq!(faces.len(), 4);
+    assert!(faces.iter().all(|face| face.cull.is_none()));
+    for dir in Dir::iter() {
+        assert!(!covers_side(Shape::Cross, Orientation::Fixed, Dir::Up, dir));
+    }
+    // every quad goes through two opposite edges of the block
+    for face in faces.iter() {
+        for (position, _) in face.vertices.iter() {
+            assert!(position.x % 2 == 0 && position.z % 2 == 0);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, HashMap};
+
+use cgmath::Vector3;
+use specs::{world::EntitiesRes, LazyUpdate, WriteStorage};
+
+use crate::{blocks::Block, chunk::Chunk, chunk_map::ChunkMap, dir::Dir};
+
+/// length of one game tick in seconds
+pub const TICK_LENGTH: f32 = 0.05;
+/// how many random blocks of every loaded chunk get a random tick per game tick
+pub const RANDOM_TICK_SPEED: usize = 3;
+/// upper bound of game ticks simulated in a single frame, so a long frame doesn't stall the game
+const MAX_TICKS_PER_FRAME: u32 = 10;
+
+/// Behaviour of a block type, registered in [`BlockBehaviours`].
+pub trait BlockBehaviour: Send + Sync {
+    /// how many game ticks after a change next to this block its scheduled tick should happen
+    fn tick_delay(&self) -> u64 {
+        1
+    }
+    fn scheduled_tick(&self, _ctx: &mut TickContext<'_, '_>, _coords: Vector3<i32>) {}
+    fn random_tick(&self, _ctx: &mut TickContext<'_, '_>, _coords: Vector3<i32>) {}
+}
+
+#[derive(Default)]
+pub struct BlockBehaviours {
+    behaviours: HashMap<Block, Box<dyn BlockBehaviour>>,
+}
+
+impl BlockBehaviours {
+    pub fn new() -> Self {
+        Self {
+            behaviours: HashMap::new(),
+        }
+    }
+    pub fn register<B: BlockBehaviour + 'static>(&mut self, block: Block, behaviour: B) {
+        self.behaviours.insert(block, Box::new(behaviour));
+    }
+    pub fn get(&self, block: Block) -> Option<&dyn BlockBehaviour> {
+        self.behaviours.get(&block).map(|b| b.as_ref())
+    }
+}
+
+/// Keeps the game clock and the block updates scheduled for future game ticks.
+#[derive(Debug, Default)]
+pub struct BlockTicks {
+    current_tick: u64,
+    accumulator: f32,
+    scheduled: BTreeMap<u64, Vec<Vector3<i32>>>,
+    /// the tick every scheduled block is due at, so a block isn't scheduled twice
+    due: HashMap<Vector3<i32>, u64>,
+    /// blocks that changed since the last game tick, their neighbours have to be scheduled
+    changed: Vec<Vector3<i32>>,
+}
+
+impl BlockTicks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    #[allow(dead_code)]
+    pub fn current_tick(&self) -> u64 {
+        self.current_tick
+    }
+    /// schedules a tick for the block at `coords` in `delay` game ticks
+    pub fn schedule(&mut self, coords: Vector3<i32>, delay: u64) {
+        let tick = self.current_tick + delay.max(1);
+        if let Some(due) = self.due.get(&coords) {
+            if *due <= tick {
+                return;
+            }
+            if let Some(scheduled) = self.scheduled.get_mut(due) {
+                scheduled.retain(|c| *c != coords);
+            }
+        }
+        self.due.insert(coords, tick);
+        self.scheduled.entry(tick).or_default().push(coords);
+    }
+    /// notifies the scheduler that the block at `coords` changed
+    pub fn block_changed(&mut self, coords: Vector3<i32>) {
+        self.changed.push(coords);
+    }
+    /// adds the time of the last frame to the clock and returns how many game ticks have to run
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        self.accumulator += dt;
+        let mut ticks = 0;
+        while self.accumulator >= TICK_LENGTH {
+            self.accumulator -= TICK_LENGTH;
+            ticks += 1;
+        }
+        ticks.min(MAX_TICKS_PER_FRAME)
+    }
+    /// starts the next game tick and returns all the blocks whose tick is due
+    pub fn next_tick(&mut self) -> Vec<Vector3<i32>> {
+        self.current_tick += 1;
+        let mut due = Vec::new();
+        while let Some(&tick) = self.scheduled.keys().next() {
+            if tick > self.current_tick {
+                break;
+            }
+            due.extend(self.scheduled.remove(&tick).unwrap());
+        }
+        for coords in &due {
+            self.due.remove(coords);
+        }
+        due
+    }
+    fn take_changed(&mut self) -> Vec<Vector3<i32>> {
+        std::mem::take(&mut self.changed)
+    }
+}
+
+/// The part of the world a [`BlockBehaviour`] is allowed to touch during a tick.
+pub struct TickContext<'a, 'b> {
+    pub chunk_map: &'a ChunkMap,
+    pub chunks: &'a mut WriteStorage<'b, Chunk>,
+    pub ticks: &'a mut BlockTicks,
+    /// for spawning entities, they are created when the world is maintained
+    pub entities: &'a EntitiesRes,
+    pub lazy: &'a LazyUpdate,
+}
+
+impl<'a, 'b> TickContext<'a, 'b> {
+    /// returns `None` if the chunk containing `coords` is not loaded
+    pub fn get_block(&self, coords: Vector3<i32>) -> Option<Block> {
+        let (chunk_coords, block_coords) = ChunkMap::coords_to_chunk_coords_and_block(coords);
+        let chunk = self.chunk_map.get_chunk(chunk_coords)?;
+        self.chunks
+            .get(chunk)
+            .map(|chunk| chunk.get_block(block_coords))
+    }
+    /// returns false if the chunk containing `coords` is not loaded
+    pub fn set_block(&mut self, coords: Vector3<i32>, block: Block) -> bool {
+        let (chunk_coords, block_coords) = ChunkMap::coords_to_chunk_coords_and_block(coords);
+        if let Some(chunk) = self
+            .chunk_map
+            .get_chunk(chunk_coords)
+            .and_then(|chunk| self.chunks.get_mut(chunk))
+        {
+            if chunk.get_block(block_coords) != block {
+                chunk.set_block(block_coords, block);
+                self.ticks.block_changed(coords);
+            }
+            true
+        } else {
+            false
+        }
+    }
+    #[allow(dead_code)]
+    pub fn schedule(&mut self, coords: Vector3<i32>, delay: u64) {
+        self.ticks.schedule(coords, delay);
+    }
+}
+
+/// schedules the blocks that changed since the last game tick and their neighbours
+pub fn schedule_changed_blocks(ctx: &mut TickContext<'_, '_>, behaviours: &BlockBehaviours) {
+    for coords in ctx.ticks.take_changed() {
+        let neighbours = Dir::iter().map(|dir| coords + Vector3::from(dir));
+        for coords in std::iter::once(coords).chain(neighbours) {
+            if let Some(behaviour) = ctx.get_block(coords).and_then(|b| behaviours.get(b)) {
+                ctx.ticks.schedule(coords, behaviour.tick_delay());
+            }
+        }
+    }
+}
+
+#[test]
+fn scheduled_ticks_are_due_in_order() {
+    let mut ticks = BlockTicks::new();
+    ticks.schedule(Vector3::new(0, 0, 0), 2);
+    ticks.schedule(Vector3::new(1, 0, 0), 1);
+    ticks.schedule(Vector3::new(2, 0, 0), 2);
+    assert_eq!(ticks.next_tick(), vec![Vector3::new(1, 0, 0)]);
+    assert_eq!(
+        ticks.next_tick(),
+        vec![Vector3::new(0, 0, 0), Vector3::new(2, 0, 0)]
+    );
+    assert!(ticks.next_tick().is_empty());
+    assert_eq!(ticks.current_tick(), 3);
+}
+#[test]
+fn scheduling_twice_keeps_the_earlier_tick() {
+    let mut ticks = BlockTicks::new();
+    ticks.schedule(Vector3::new(0, 0, 0), 3);
+    ticks.schedule(Vector3::new(0, 0, 0), 1);
+    ticks.schedule(Vector3::new(0, 0, 0), 2);
+    assert_eq!(ticks.next_tick(), vec![Vector3::new(0, 0, 0)]);
+    assert!(ticks.next_tick().is_empty());
+    assert!(ticks.next_tick().is_empty());
+}
+#[test]
+fn advance_runs_whole_ticks() {
+    let mut ticks = BlockTicks::new();
+    assert_eq!(ticks.advance(TICK_LENGTH * 0.5), 0);
+    assert_eq!(ticks.advance(TICK_LENGTH * 2.0), 2);
+    assert_eq!(ticks.advance(TICK_LENGTH * 100.0), MAX_TICKS_PER_FRAME);
+}
@@ -1,9 +1,15 @@
+use crate::block_model::Shape;
+use crate::climate::Climate;
+use crate::dir::Dir;
 use cgmath::Vector2;
-use image::DynamicImage;
+use image::{DynamicImage, GenericImageView, RgbaImage};
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
 
+/// the number of uv coordinates along each axis of a texture
+pub const UV_GRID: usize = 3;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 struct UvRect {
     top_left_y: f32,
@@ -12,27 +18,51 @@ struct UvRect {
     down_right_y: f32,
 }
 
+/// a texture made of frames below each other, `rect` is the first of them
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Animation {
+    frames: u32,
+    frame_time: f32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct Texture {
     pub name: String,
     pub rect: UvRect,
+    #[serde(default)]
+    pub animation: Option<Animation>,
 }
 
+/// the textures of a block, faces without their own texture use `side` if they are horizontal
+/// or `top` if they are on top, and `base` otherwise
 pub struct UvBlock {
     pub name: String,
     pub base: usize,
     pub top: Option<usize>,
     pub side: Option<usize>,
-    pub index: usize,
+    /// the textures of single faces of the unturned block, in the order of Dir
+    pub faces: [Option<usize>; 6],
+}
+impl UvBlock {
+    pub fn texture(&self, dir: Dir) -> usize {
+        let fallback = match dir {
+            Dir::North | Dir::South | Dir::East | Dir::West => self.side,
+            Dir::Up => self.top,
+            Dir::Down => None,
+        };
+        self.faces[dir as usize].or(fallback).unwrap_or(self.base)
+    }
 }
 
 pub struct Atlas {
     pub atlas: DynamicImage,
     pub blocks: Vec<UvBlock>,
     pub all_uvs: Vec<[f32; 2]>,
+    /// for every uv: the number of frames, the seconds per frame and the height of a frame
+    pub all_animations: Vec<[f32; 4]>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Block {
     Empty = 0,
     Dirt = 1,
@@ -40,6 +70,147 @@ pub enum Block {
     Sand = 3,
     Water = 4,
     Grass = 5,
+    Log = 6,
+    StoneSlab = 7,
+    StoneStairs = 8,
+    TallGrass = 9,
+    Furnace = 10,
+    CoalOre = 11,
+    IronOre = 12,
+    GoldOre = 13,
+    Leaves = 14,
+}
+
+/// how a block is turned when it is placed, stored as a facing per block in the chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// always placed the same way, facing up
+    Fixed,
+    /// the top of the block points away from the face it was placed against, like logs
+    Axis,
+    /// the front of the block (its north side) faces the player, like stairs and furnaces
+    Horizontal,
+}
+
+/// the pass a block is drawn in, every chunk has a mesh for each of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderLayer {
+    Opaque = 0,
+    /// pixels are either fully opaque or not drawn at all, like plants
+    Cutout = 1,
+    /// blended with what is behind, drawn after everything else, like water
+    Translucent = 2,
+}
+
+impl Block {
+    pub const ALL: [Block; 15] = [
+        Block::Empty,
+        Block::Dirt,
+        Block::Stone,
+        Block::Sand,
+        Block::Water,
+        Block::Grass,
+        Block::Log,
+        Block::StoneSlab,
+        Block::StoneStairs,
+        Block::TallGrass,
+        Block::Furnace,
+        Block::CoalOre,
+        Block::IronOre,
+        Block::GoldOre,
+        Block::Leaves,
+    ];
+    pub fn name(self) -> &'static str {
+        match self {
+            Block::Empty => "empty",
+            Block::Dirt => "dirt",
+            Block::Stone => "stone",
+            Block::Sand => "sand",
+            Block::Water => "water",
+            Block::Grass => "grass",
+            Block::Log => "log",
+            Block::StoneSlab => "stone_slab",
+            Block::StoneStairs => "stone_stairs",
+            Block::TallGrass => "tall_grass",
+            Block::Furnace => "furnace",
+            Block::CoalOre => "coal_ore",
+            Block::IronOre => "iron_ore",
+            Block::GoldOre => "gold_ore",
+            Block::Leaves => "leaves",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Block> {
+        Self::ALL.iter().copied().find(|block| block.name() == name)
+    }
+    /// seconds it takes to break this block by hand
+    pub fn hardness(self) -> f32 {
+        match self {
+            Block::Empty | Block::Water | Block::TallGrass => 0.0,
+            Block::Dirt | Block::Sand => 0.75,
+            Block::Grass => 0.9,
+            Block::Stone | Block::StoneSlab | Block::StoneStairs => 4.0,
+            Block::Log => 2.0,
+            Block::Furnace => 3.5,
+            Block::CoalOre => 4.5,
+            Block::IronOre | Block::GoldOre => 5.0,
+            Block::Leaves => 0.3,
+        }
+    }
+    /// the sound played when this block is broken or placed
+    pub fn sound(self) -> Option<&'static str> {
+        match self {
+            Block::Dirt | Block::Grass | Block::Log | Block::TallGrass | Block::Leaves => {
+                Some("./dirt.json")
+            }
+            Block::Sand => Some("./sand.json"),
+            Block::Stone
+            | Block::StoneSlab
+            | Block::StoneStairs
+            | Block::Furnace
+            | Block::CoalOre
+            | Block::IronOre
+            | Block::GoldOre => Some("./stone.json"),
+            Block::Empty | Block::Water => None,
+        }
+    }
+    pub fn orientation(self) -> Orientation {
+        match self {
+            Block::Log => Orientation::Axis,
+            Block::StoneStairs | Block::Furnace => Orientation::Horizontal,
+            _ => Orientation::Fixed,
+        }
+    }
+    pub fn shape(self) -> Shape {
+        match self {
+            Block::StoneSlab => Shape::Slab,
+            Block::StoneStairs => Shape::Stairs,
+            Block::TallGrass => Shape::Cross,
+            _ => Shape::Cube,
+        }
+    }
+    pub fn render_layer(self) -> RenderLayer {
+        match self {
+            Block::Water => RenderLayer::Translucent,
+            Block::TallGrass | Block::Leaves => RenderLayer::Cutout,
+            _ => RenderLayer::Opaque,
+        }
+    }
+    /// whether the texture of this side is gray and gets the grass color of the column
+    pub fn is_tinted(self, side: Dir) -> bool {
+        match self {
+            Block::Grass => side == Dir::Up,
+            Block::TallGrass | Block::Leaves => true,
+            _ => false,
+        }
+    }
+    /// whether this block turns into a falling entity when there is nothing below it
+    pub fn falls(self) -> bool {
+        matches!(self, Block::Sand)
+    }
+    /// whether a falling block or a fluid can take the place of this block
+    pub fn is_replaceable(self) -> bool {
+        matches!(self, Block::Empty | Block::Water | Block::TallGrass)
+    }
 }
 
 impl From<u32> for Block {
@@ -51,6 +222,15 @@ impl From<u32> for Block {
             3 => Self::Sand,
             4 => Self::Water,
             5 => Self::Grass,
+            6 => Self::Log,
+            7 => Self::StoneSlab,
+            8 => Self::StoneStairs,
+            9 => Self::TallGrass,
+            10 => Self::Furnace,
+            11 => Self::CoalOre,
+            12 => Self::IronOre,
+            13 => Self::GoldOre,
+            14 => Self::Leaves,
             _ => {
                 panic!("conversion from u32 ({}) to Block failed", n)
             }
@@ -69,99 +249,226 @@ impl Atlas {
             dir.insert(texture.name.clone(), i);
         }
 
-        let mut blocks = vec![
+        let blocks = vec![
             UvBlock {
                 name: "emtpy".into(),
                 base: *dir.get("stone").unwrap(), // just a filler for empty
                 top: None,
                 side: None,
-                index: 0,
+                faces: [None; 6],
             },
             UvBlock {
                 name: "dirt".into(),
                 base: *dir.get("dirt").unwrap(),
                 top: None,
                 side: None,
-                index: 0,
+                faces: [None; 6],
             },
             UvBlock {
                 name: "stone".into(),
                 base: *dir.get("stone").unwrap(),
                 top: None,
                 side: None,
-                index: 0,
+                faces: [None; 6],
             },
             UvBlock {
                 name: "sand".into(),
                 base: *dir.get("sand").unwrap(),
                 top: None,
                 side: None,
-                index: 0,
+                faces: [None; 6],
             },
             UvBlock {
                 name: "water".into(),
                 base: *dir.get("water").unwrap(),
                 top: None,
                 side: None,
-                index: 0,
+                faces: [None; 6],
             },
             UvBlock {
                 name: "grass".into(),
                 base: *dir.get("dirt").unwrap(),
                 top: Some(*dir.get("grass_top").unwrap()),
                 side: Some(*dir.get("grass_side").unwrap()),
-                index: 0,
+                faces: [None; 6],
+            },
+            UvBlock {
+                name: "log".into(),
+                base: *dir.get("log_top").unwrap(),
+                top: None,
+                side: Some(*dir.get("log_side").unwrap()),
+                faces: [None; 6],
+            },
+            UvBlock {
+                name: "stone_slab".into(),
+                base: *dir.get("stone").unwrap(),
+                top: None,
+                side: None,
+                faces: [None; 6],
+            },
+            UvBlock {
+                name: "stone_stairs".into(),
+                base: *dir.get("stone").unwrap(),
+                top: None,
+                side: None,
+                faces: [None; 6],
+            },
+            UvBlock {
+                name: "tall_grass".into(),
+                base: *dir.get("tall_grass").unwrap(),
+                top: None,
+                side: None,
+                faces: [None; 6],
+            },
+            UvBlock {
+                name: "furnace".into(),
+                base: *dir.get("furnace_top").unwrap(),
+                top: None,
+                side: Some(*dir.get("furnace_side").unwrap()),
+                // the front is north
+                faces: [
+                    Some(*dir.get("furnace_front").unwrap()),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                ],
+            },
+            UvBlock {
+                name: "coal_ore".into(),
+                base: *dir.get("coal_ore").unwrap(),
+                top: None,
+                side: None,
+                faces: [None; 6],
+            },
+            UvBlock {
+                name: "iron_ore".into(),
+                base: *dir.get("iron_ore").unwrap(),
+                top: None,
+                side: None,
+                faces: [None; 6],
+            },
+            UvBlock {
+                name: "gold_ore".into(),
+                base: *dir.get("gold_ore").unwrap(),
+                top: None,
+                side: None,
+                faces: [None; 6],
+            },
+            UvBlock {
+                name: "leaves".into(),
+                base: *dir.get("leaves").unwrap(),
+                top: None,
+                side: None,
+                faces: [None; 6],
             },
         ];
 
+        // a 3x3 grid over every texture, so faces of half blocks get half a texture
         let mut all_uvs: Vec<[f32; 2]> = vec![];
-        for block in &mut blocks {
-            block.index = all_uvs.len();
-            let mut add = |i: usize| {
-                let rect = &textures[i].rect;
-                all_uvs.push(Vector2::new(rect.top_left_x, rect.top_left_y).into());
-                all_uvs.push(Vector2::new(rect.down_right_x, rect.top_left_y).into());
-                all_uvs.push(Vector2::new(rect.top_left_x, rect.down_right_y).into());
-                all_uvs.push(Vector2::new(rect.down_right_x, rect.down_right_y).into());
+        let mut all_animations: Vec<[f32; 4]> = vec![];
+        for texture in &textures {
+            let rect = &texture.rect;
+            let animation = match texture.animation {
+                Some(animation) => [
+                    animation.frames as f32,
+                    animation.frame_time,
+                    rect.down_right_y - rect.top_left_y,
+                    0.0,
+                ],
+                // a single frame that never moves
+                None => [1.0, 1.0, 0.0, 0.0],
             };
-            {
-                add(block.base);
-            }
-            if let Some(side) = block.side {
-                add(side);
-            } else {
-                add(block.base);
-            }
-            if let Some(top) = block.top {
-                add(top);
-            } else {
-                add(block.base);
+            for v in 0..UV_GRID {
+                for u in 0..UV_GRID {
+                    let t = |a: f32, b: f32, n: usize| a + (b - a) * n as f32 / 2.0;
+                    all_uvs.push(
+                        Vector2::new(
+                            t(rect.top_left_x, rect.down_right_x, u),
+                            t(rect.top_left_y, rect.down_right_y, v),
+                        )
+                        .into(),
+                    );
+                    all_animations.push(animation);
+                }
             }
         }
         Self {
             atlas,
             blocks,
             all_uvs,
+            all_animations,
         }
     }
-    pub fn uvs_of_block_index(&self, block: usize, side: Side) -> [u16; 4] {
-        let block = &self.blocks[block];
-        let offset = match side {
-            Side::Base => 0,
-            Side::Top => 8,
-            Side::Side => 4,
-        };
-        [
-            block.index as u16 + offset,
-            block.index as u16 + offset + 1,
-            block.index as u16 + offset + 2,
-            block.index as u16 + offset + 3,
-        ]
+    /// the front (north) texture of a block, flipped vertically for the flat meshes
+    pub fn block_icon(&self, block: Block) -> RgbaImage {
+        let indices = self.uvs_of_block_index(block as usize, Dir::North);
+        let top_left = self.all_uvs[indices[0] as usize];
+        let down_right = self.all_uvs[indices[UV_GRID * UV_GRID - 1] as usize];
+        let (width, height) = self.atlas.dimensions();
+        let x = (top_left[0] * width as f32).round() as u32;
+        let y = (top_left[1] * height as f32).round() as u32;
+        let w = (down_right[0] * width as f32).round() as u32 - x;
+        let h = (down_right[1] * height as f32).round() as u32 - y;
+        let mut icon = image::imageops::flip_vertical(&self.atlas.crop_imm(x, y, w, h).to_rgba8());
+        if block.is_tinted(Dir::North) {
+            let tint = Climate::default().grass_color();
+            for pixel in icon.pixels_mut() {
+                for (c, t) in pixel.0.iter_mut().zip(tint.iter()) {
+                    *c = (*c as u32 * *t as u32 / 255) as u8;
+                }
+            }
+        }
+        icon
+    }
+    /// the uv indices of a face of the unturned block, in rows from the top left of the texture,
+    /// with a row and a column at the middle of the texture
+    pub fn uvs_of_block_index(&self, block: usize, dir: Dir) -> [u16; UV_GRID * UV_GRID] {
+        let first = self.blocks[block].texture(dir) * UV_GRID * UV_GRID;
+        let mut uvs = [0; UV_GRID * UV_GRID];
+        for (i, uv) in uvs.iter_mut().enumerate() {
+            *uv = (first + i) as u16;
+        }
+        uvs
     }
 }
 
-pub enum Side {
-    Base,
-    Top,
-    Side,
+#[test]
+fn water_is_animated() {
+    let textures: Vec<Texture> = serde_json::from_str(include_str!("../map.json")).unwrap();
+    let water = textures.iter().find(|t| t.name == "water").unwrap();
+    let animation = water.animation.unwrap();
+    assert!(animation.frames > 1);
+    assert!(animation.frame_time > 0.0);
+    // the rect is only the first frame of the strip
+    let stone = textures.iter().find(|t| t.name == "stone").unwrap();
+    assert!(stone.animation.is_none());
+    let height = |t: &Texture| t.rect.down_right_y - t.rect.top_left_y;
+    assert!((height(water) - height(stone)).abs() < 1e-6);
+}
+#[test]
+fn faces_fall_back_to_side_top_and_base() {
+    let furnace = UvBlock {
+        name: "furnace".into(),
+        base: 0,
+        top: None,
+        side: Some(1),
+        faces: [Some(2), None, None, None, None, None],
+    };
+    assert_eq!(furnace.texture(Dir::North), 2);
+    assert_eq!(furnace.texture(Dir::South), 1);
+    assert_eq!(furnace.texture(Dir::East), 1);
+    assert_eq!(furnace.texture(Dir::Up), 0);
+    assert_eq!(furnace.texture(Dir::Down), 0);
+    let grass = UvBlock {
+        name: "grass".into(),
+        base: 0,
+        top: Some(3),
+        side: Some(4),
+        faces: [None; 6],
+    };
+    assert_eq!(grass.texture(Dir::Up), 3);
+    assert_eq!(grass.texture(Dir::West), 4);
+    assert_eq!(grass.texture(Dir::Down), 0);
 }
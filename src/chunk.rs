@@ -1,4 +1,6 @@
 use crate::blocks::Block;
+use crate::climate::{blended_grass_colors, Climate};
+use crate::dir::Dir;
 use crate::neighbours::Neighbours;
 use cgmath::Vector3;
 use specs::Component;
@@ -9,10 +11,18 @@ pub const CHUNK_SIZE: usize = 16;
 pub struct Chunk {
     pub position: Vector3<i32>,
     pub blocks: Box<[[[Block; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]>,
+    /// which way every block faces, only used by blocks that have an Orientation
+    pub facings: Box<[[[Dir; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]>,
+    /// the climate of every column, by x and z
+    pub climates: Box<[[Climate; CHUNK_SIZE]; CHUNK_SIZE]>,
+    /// the color grass and leaves are tinted with in every column, by x and z
+    pub grass_colors: Box<[[[u8; 3]; CHUNK_SIZE]; CHUNK_SIZE]>,
     /// whether the mesh of this chunk has to be regenerated
     pub regenerate_mesh: bool,
     /// all the neighbours that have to updated
     pub update_neighbours: Neighbours<()>,
+    /// whether the blocks changed since the chunk was last saved
+    pub unsaved: bool,
 }
 
 impl Chunk {
@@ -20,39 +30,84 @@ impl Chunk {
         Self {
             position,
             blocks: Box::new([[[Block::Empty; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]),
+            facings: Box::new([[[Dir::Up; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]),
+            climates: Box::new([[Climate::default(); CHUNK_SIZE]; CHUNK_SIZE]),
+            grass_colors: Box::new([[Climate::default().grass_color(); CHUNK_SIZE]; CHUNK_SIZE]),
             regenerate_mesh: false,
             update_neighbours: Neighbours::new(),
+            unsaved: false,
         }
     }
+    /// sets the climate of the columns of this chunk from the world seed
+    pub fn generate_climate(&mut self, seed: u64) {
+        let size = CHUNK_SIZE as i32;
+        let (x, z) = (self.position.x * size, self.position.z * size);
+        for (cx, row) in self.climates.iter_mut().enumerate() {
+            for (cz, climate) in row.iter_mut().enumerate() {
+                *climate = Climate::at(seed, x + cx as i32, z + cz as i32);
+            }
+        }
+        *self.grass_colors = blended_grass_colors(seed, x, z);
+    }
     pub fn get_block(&self, pos: Vector3<u16>) -> Block {
         let x = pos.x as usize;
         let y = pos.y as usize;
         let z = pos.z as usize;
         self.blocks[x][y][z]
     }
+    pub fn get_facing(&self, pos: Vector3<u16>) -> Dir {
+        self.facings[pos.x as usize][pos.y as usize][pos.z as usize]
+    }
     pub fn set_block(&mut self, pos: Vector3<u16>, block: Block) {
+        self.set_block_facing(pos, block, Dir::Up);
+    }
+    pub fn set_block_facing(&mut self, pos: Vector3<u16>, block: Block, facing: Dir) {
+        if self.write_block(pos, block, facing) {
+            self.mark_changed(&Self::sides_touched(pos));
+        }
+    }
+    /// changes the block without flagging anything, returns whether it changed
+    pub fn write_block(&mut self, pos: Vector3<u16>, block: Block, facing: Dir) -> bool {
         let x = pos.x as usize;
         let y = pos.y as usize;
         let z = pos.z as usize;
         let b = &mut self.blocks[x][y][z];
-        if *b != block {
+        let f = &mut self.facings[x][y][z];
+        if *b != block || *f != facing {
             *b = block;
-            self.regenerate_mesh = true;
-            if x == 0 {
-                self.update_neighbours.west = Some(());
-            } else if x == CHUNK_SIZE - 1 {
-                self.update_neighbours.east = Some(());
-            }
-            if y == 0 {
-                self.update_neighbours.down = Some(());
-            } else if y == CHUNK_SIZE - 1 {
-                self.update_neighbours.up = Some(());
-            }
-            if z == 0 {
-                self.update_neighbours.south = Some(());
-            } else if z == CHUNK_SIZE - 1 {
-                self.update_neighbours.north = Some(());
-            }
+            *f = facing;
+            true
+        } else {
+            false
+        }
+    }
+    /// the sides of the chunk the block at `pos` lies on, the neighbours there have to be updated when it changes
+    pub fn sides_touched(pos: Vector3<u16>) -> Neighbours<()> {
+        let mut sides = Neighbours::new();
+        let last = CHUNK_SIZE as u16 - 1;
+        if pos.x == 0 {
+            sides.west = Some(());
+        } else if pos.x == last {
+            sides.east = Some(());
+        }
+        if pos.y == 0 {
+            sides.down = Some(());
+        } else if pos.y == last {
+            sides.up = Some(());
+        }
+        if pos.z == 0 {
+            sides.south = Some(());
+        } else if pos.z == last {
+            sides.north = Some(());
+        }
+        sides
+    }
+    /// flags the chunk to be remeshed and saved and the neighbours on `sides` to be updated
+    pub fn mark_changed(&mut self, sides: &Neighbours<()>) {
+        self.regenerate_mesh = true;
+        self.unsaved = true;
+        for (dir, _) in sides.iter() {
+            self.update_neighbours.set(dir, ());
         }
     }
 }
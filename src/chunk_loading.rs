@@ -0,0 +1,126 @@
+use crate::chunk_map::ChunkMap;
+use crate::components::{ChunkTicket, Position};
+use cgmath::{MetricSpace, Vector3};
+use specs::{Join, ReadStorage};
+use std::collections::{HashMap, HashSet};
+
+/// how many columns further than its radius a ticket keeps loaded chunks,
+/// so walking back and forth over the edge doesn't load and unload the same chunks all the time
+const UNLOAD_MARGIN: i32 = 6;
+
+/// tickets of the parts of the game that are no entity, like the spawn area, by the name of who holds them
+#[derive(Debug, Default)]
+pub struct ChunkTickets {
+    tickets: HashMap<&'static str, (Vector3<i32>, ChunkTicket)>,
+}
+
+impl ChunkTickets {
+    /// holds the ticket `name` around the chunk column at `column`, replacing the one it held before
+    pub fn set(&mut self, name: &'static str, column: Vector3<i32>, ticket: ChunkTicket) {
+        self.tickets.insert(name, (column_of(column), ticket));
+    }
+}
+
+fn column_of(mut chunk: Vector3<i32>) -> Vector3<i32> {
+    // whole columns are loaded and removed together
+    chunk.y = 0;
+    chunk
+}
+
+/// every ticket with the chunk column it is around, from entities with a Position and from ChunkTickets
+pub fn anchors(
+    tickets: &ChunkTickets,
+    entity_tickets: &ReadStorage<'_, ChunkTicket>,
+    positions: &ReadStorage<'_, Position>,
+) -> Vec<(Vector3<i32>, ChunkTicket)> {
+    let mut anchors: Vec<_> = tickets.tickets.values().copied().collect();
+    for (ticket, position) in (entity_tickets, positions).join() {
+        let column = column_of(ChunkMap::f_coords_to_chunk_coords(position.0));
+        anchors.push((column, *ticket));
+    }
+    anchors
+}
+
+/// the columns the tickets want that `is_loaded` says aren't there,
+/// the ones of tickets with a higher priority first and the closest first within a priority
+pub fn columns_to_load(
+    anchors: &[(Vector3<i32>, ChunkTicket)],
+    is_loaded: impl Fn(Vector3<i32>) -> bool,
+) -> Vec<Vector3<i32>> {
+    let mut wanted = Vec::new();
+    for (center, ticket) in anchors {
+        let range = ticket.radius;
+        for x in -range..range + 1 {
+            for z in -range..range + 1 {
+                let column = center + Vector3::new(x, 0, z);
+                if column.distance2(*center) < range * range && !is_loaded(column) {
+                    wanted.push((-ticket.priority, column.distance2(*center), column));
+                }
+            }
+        }
+    }
+    wanted.sort_by_key(|&(priority, distance, column)| (priority, distance, column.x, column.z));
+    let mut seen = HashSet::new();
+    wanted
+        .into_iter()
+        .map(|(_, _, column)| column)
+        .filter(|column| seen.insert(*column))
+        .collect()
+}
+
+/// whether any ticket still keeps the chunk at `chunk` loaded, with the margin around its radius
+pub fn keeps_loaded(anchors: &[(Vector3<i32>, ChunkTicket)], chunk: Vector3<i32>) -> bool {
+    keeps_loaded_near(anchors, chunk, 0)
+}
+
+/// whether any ticket keeps a chunk up to `reach` columns from `chunk` loaded
+pub fn keeps_loaded_near(
+    anchors: &[(Vector3<i32>, ChunkTicket)],
+    chunk: Vector3<i32>,
+    reach: i32,
+) -> bool {
+    let column = column_of(chunk);
+    anchors.iter().any(|(center, ticket)| {
+        let range = ticket.radius + UNLOAD_MARGIN + reach;
+        column.distance2(*center) < range * range
+    })
+}
+
+#[test]
+fn tickets_load_their_union_and_unload_late() {
+    let near = ChunkTicket {
+        radius: 2,
+        priority: 0,
+    };
+    let important = ChunkTicket {
+        radius: 1,
+        priority: 5,
+    };
+    let anchors = [
+        (Vector3::new(0, 0, 0), near),
+        (Vector3::new(1, 0, 0), near),
+        (Vector3::new(20, 0, 0), important),
+    ];
+    let columns = columns_to_load(&anchors, |_| false);
+    // the overlap of the first two is only loaded once, the important ticket goes first
+    assert_eq!(columns.len(), 9 + 3 + 1);
+    assert_eq!(columns[0], Vector3::new(20, 0, 0));
+    assert_eq!(columns[1], Vector3::new(0, 0, 0));
+    let loaded = |column: Vector3<i32>| column.x <= 0;
+    assert!(columns_to_load(&anchors, loaded)
+        .iter()
+        .all(|column| column.x > 0));
+    // a chunk outside the radius isn't loaded, but stays when it already is
+    let outside = Vector3::new(-3, 1, 0);
+    assert!(!columns_to_load(&anchors, |_| false).contains(&column_of(outside)));
+    assert!(keeps_loaded(&anchors, outside));
+    assert!(!keeps_loaded(
+        &anchors,
+        Vector3::new(-2 - UNLOAD_MARGIN, 0, 0)
+    ));
+    assert!(keeps_loaded_near(
+        &anchors,
+        Vector3::new(-2 - UNLOAD_MARGIN, 0, 0),
+        1
+    ));
+}
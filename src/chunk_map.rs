@@ -31,9 +31,6 @@ impl ChunkMap {
     pub fn get_chunk(&self, pos: Vector3<i32>) -> Option<Entity> {
         self.chunks.get(&pos).copied()
     }
-    pub fn get_chunk_from_coords(&self, coords: Vector3<i32>) -> Option<Entity> {
-        self.get_chunk(Self::coords_to_chunk_coords(coords))
-    }
     pub fn set_chunk(&mut self, pos: Vector3<i32>, chunk: Entity) {
         self.chunks.insert(pos, chunk);
     }
@@ -58,17 +55,11 @@ impl ChunkMap {
     }
     pub fn coords_to_chunk_coords(coords: Vector3<i32>) -> Vector3<i32> {
         let cs = CHUNK_SIZE as i32;
-        let mut cc = coords / cs;
-        if coords.x < 0 {
-            cc.x -= 1
-        }
-        if coords.y < 0 {
-            cc.y -= 1
-        }
-        if coords.z < 0 {
-            cc.z -= 1
-        }
-        cc
+        Vector3::new(
+            coords.x.div_euclid(cs),
+            coords.y.div_euclid(cs),
+            coords.z.div_euclid(cs),
+        )
     }
     pub fn coords_to_chunk_coords_and_block(coords: Vector3<i32>) -> (Vector3<i32>, Vector3<u16>) {
         let mut chunk_coords: Vector3<i32> = coords / CHUNK_SIZE as i32;
@@ -152,4 +143,19 @@ fn coords_to_chunk_coords() {
         ChunkMap::coords_to_chunk_coords(Vector3::new(17, 0, -5)),
         Vector3::new(1, 0, -1)
     );
+    // the first block of a chunk on the negative side
+    for &coords in &[
+        Vector3::new(-16, 0, 3),
+        Vector3::new(3, 0, -16),
+        Vector3::new(-32, -16, -17),
+    ] {
+        assert_eq!(
+            ChunkMap::coords_to_chunk_coords(coords),
+            ChunkMap::coords_to_chunk_coords_and_block(coords).0
+        );
+    }
+    assert_eq!(
+        ChunkMap::coords_to_chunk_coords(Vector3::new(-16, 0, -16)),
+        Vector3::new(-1, 0, -1)
+    );
 }
@@ -3,6 +3,7 @@
 layout(location=0) in vec2 v_tex_coords;
 layout(location=1) in vec3 in_position;
 layout(location=2) in vec3 in_normal;
+layout(location=3) in vec3 in_tint;
 
 layout(location=0) out vec4 f_color;
 
@@ -54,7 +55,13 @@ layout(set=1, binding=0) uniform texture2D t_diffuse;
 layout(set=1, binding=1) uniform sampler s_diffuse;
 
 void main() {
-    vec4 object_color = texture(sampler2D(t_diffuse, s_diffuse), v_tex_coords);
+    vec4 object_color = texture(sampler2D(t_diffuse, s_diffuse), v_tex_coords) * vec4(in_tint, 1.0);
+#ifdef CUTOUT
+    // cutout blocks like plants are either fully there or not at all
+    if (object_color.a < 0.5) {
+        discard;
+    }
+#endif
     if (lighting_enabled != 0) {
         vec4 color = ambient_color;
         vec3 view_dir = normalize(camera_pos.xyz - in_position.xyz);
@@ -101,4 +108,6 @@ void main() {
     } else {
         f_color = object_color * ambient_color;
     }
+    // the lights only change the color, translucent blocks keep the alpha of their texture
+    f_color.a = object_color.a;
 }
@@ -1,7 +1,8 @@
+use crate::blocks::RenderLayer;
 use crate::chunk::CHUNK_SIZE;
 use crate::chunk_middle_ware::ChunkVertex;
 use bytemuck::{Pod, Zeroable};
-use cgmath::Vector3;
+use cgmath::{InnerSpace, Vector3};
 use finger_paint_wgpu::texture::Texture;
 use finger_paint_wgpu::wgpu::util::{BufferInitDescriptor, DeviceExt};
 use finger_paint_wgpu::wgpu::{
@@ -21,8 +22,9 @@ pub struct ChunkUniform {
 
 pub struct ChunkMesh {
     device: Arc<Device>,
-    pub vertices: Vec<ChunkVertex>,
-    pub vertex_buffer: wgpu::Buffer,
+    /// the vertices of every RenderLayer
+    pub vertices: [Vec<ChunkVertex>; 3],
+    pub vertex_buffers: [wgpu::Buffer; 3],
     pub uniform: ChunkUniform,
     pub uniform_buffer: wgpu::Buffer,
     pub bind_group: BindGroup,
@@ -33,12 +35,13 @@ pub struct ChunkMesh {
 impl ChunkMesh {
     pub fn new(
         device: Arc<Device>,
-        vertices: Vec<ChunkVertex>,
+        vertices: [Vec<ChunkVertex>; 3],
         position: Vector3<i32>,
         bind_group_layout: &BindGroupLayout,
         uv_buffer: &Buffer,
+        animation_buffer: &Buffer,
     ) -> Self {
-        let vertex_buffer = Self::create_vertex_buffer(&device, &vertices);
+        let vertex_buffers = Self::create_vertex_buffers(&device, &vertices);
         let uniform = ChunkUniform {
             position: position.into(),
             chunk_size: CHUNK_SIZE as f32,
@@ -63,13 +66,21 @@ impl ChunkMesh {
                         size: None,
                     },
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: animation_buffer,
+                        offset: 0,
+                        size: None,
+                    },
+                },
             ],
             label: None,
         });
         Self {
             device,
             vertices,
-            vertex_buffer,
+            vertex_buffers,
             uniform,
             uniform_buffer,
             bind_group,
@@ -78,14 +89,26 @@ impl ChunkMesh {
         }
     }
     pub fn update_vertices(&mut self) {
-        self.vertex_buffer = Self::create_vertex_buffer(&self.device, &self.vertices);
+        self.vertex_buffers = Self::create_vertex_buffers(&self.device, &self.vertices);
     }
-    fn create_vertex_buffer(device: &Device, vertices: &[ChunkVertex]) -> Buffer {
-        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Cubes Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: BufferUsage::VERTEX,
-        })
+    fn create_vertex_buffers(device: &Device, vertices: &[Vec<ChunkVertex>; 3]) -> [Buffer; 3] {
+        let create = |vertices: &[ChunkVertex]| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Cubes Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: BufferUsage::VERTEX,
+            })
+        };
+        [
+            create(&vertices[0]),
+            create(&vertices[1]),
+            create(&vertices[2]),
+        ]
+    }
+    /// the center of the chunk in the world
+    pub fn center(&self) -> Vector3<f32> {
+        let position = Vector3::from(self.uniform.position).cast::<f32>().unwrap();
+        (position + Vector3::new(0.5, 0.5, 0.5)) * self.uniform.chunk_size
     }
     pub fn bind_group_layout(device: &Device) -> BindGroupLayout {
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -113,6 +136,16 @@ impl ChunkMesh {
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         })
     }
@@ -148,10 +181,13 @@ pub struct ChunkMeshMiddleWare {
     device: Arc<Device>,
     shadow_pipeline: RenderPipeline,
     forward_pipeline: RenderPipeline,
+    cutout_pipeline: RenderPipeline,
+    translucent_pipeline: RenderPipeline,
     bind_group_layout: BindGroupLayout,
     diffuse_bind_group_layout: BindGroupLayout,
     bind_group: Option<BindGroup>,
     uv_buffer: Buffer,
+    animation_buffer: Buffer,
     texture: Option<Texture>,
 }
 impl ChunkMeshMiddleWare {
@@ -164,6 +200,26 @@ impl ChunkMeshMiddleWare {
             meshes: chunks,
         }
     }
+    /// the translucent parts of the chunks, which have to be rendered after everything else,
+    /// sorted from back to front as seen from `eye`
+    pub fn prepare_translucent<'a, 'b, I>(
+        &'a self,
+        chunks: I,
+        eye: Vector3<f32>,
+    ) -> ChunkMeshTranslucentRenderable<'a, 'b>
+    where
+        I: Iterator<Item = &'b ChunkMesh>,
+    {
+        let mut meshes: Vec<_> = chunks
+            .filter(|mesh| !mesh.vertices[RenderLayer::Translucent as usize].is_empty())
+            .map(|mesh| ((mesh.center() - eye).magnitude2(), mesh))
+            .collect();
+        meshes.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap());
+        ChunkMeshTranslucentRenderable {
+            inner: self,
+            meshes: meshes.into_iter().map(|(_, mesh)| mesh).collect(),
+        }
+    }
 }
 pub struct ChunkMeshMiddleWareRenderable<'a, 'b, I>
 where
@@ -188,16 +244,51 @@ where
             self.inner.bind_group.as_ref().unwrap(),
             self.meshes.clone(),
             2,
-        )
+            RenderLayer::Opaque,
+        );
+        render_chunk_meshes(
+            &self.inner.cutout_pipeline,
+            render_pass,
+            self.inner.bind_group.as_ref().unwrap(),
+            self.meshes.clone(),
+            2,
+            RenderLayer::Cutout,
+        );
     }
 
+    /// translucent blocks don't cast shadows
     fn render_shadow_pass<'c>(&'c self, render_pass: &mut RenderPass<'c>) {
+        for layer in [RenderLayer::Opaque, RenderLayer::Cutout].iter() {
+            render_chunk_meshes(
+                &self.inner.shadow_pipeline,
+                render_pass,
+                self.inner.bind_group.as_ref().unwrap(),
+                self.meshes.clone(),
+                1,
+                *layer,
+            );
+        }
+    }
+}
+
+pub struct ChunkMeshTranslucentRenderable<'a, 'b> {
+    inner: &'a ChunkMeshMiddleWare,
+    meshes: Vec<&'b ChunkMesh>,
+}
+
+impl<'a, 'b> MiddleWare for ChunkMeshTranslucentRenderable<'a, 'b> {
+    fn name(&self) -> &str {
+        "ChunkMeshTranslucent"
+    }
+
+    fn render<'pass>(&'pass self, render_pass: &mut RenderPass<'pass>) {
         render_chunk_meshes(
-            &self.inner.shadow_pipeline,
+            &self.inner.translucent_pipeline,
             render_pass,
             self.inner.bind_group.as_ref().unwrap(),
-            self.meshes.clone(),
-            1,
+            self.meshes.iter().copied(),
+            2,
+            RenderLayer::Translucent,
         );
     }
 }
@@ -247,47 +338,109 @@ impl finger_paint_wgpu::MiddleWareConstructor for ChunkMeshMiddleWare {
                 ],
                 push_constant_ranges: &[],
             });
-        let forward_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some(&format!("forward pipeline: {}", "chunk mesh")),
-            layout: Some(&forward_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &vs_shader,
-                entry_point: "main",
-                buffers: &[ChunkVertex::desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &fs_shader,
-                entry_point: "main",
-                targets: &[finger_paint_wgpu::wgpu::ColorTargetState::from(
-                    *renderer.format(),
-                )],
-            }),
-            primitive: wgpu::PrimitiveState {
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: wgpu::CullMode::Back,
-                ..Default::default()
+        let fs_cutout_shader = renderer.load_spirv(include_bytes!("fs_cutout.glsl.spv"));
+        let forward_pipeline = create_forward_pipeline(
+            renderer,
+            &forward_pipeline_layout,
+            &vs_shader,
+            &fs_shader,
+            "chunk mesh",
+            wgpu::BlendState::REPLACE,
+            true,
+        );
+        let cutout_pipeline = create_forward_pipeline(
+            renderer,
+            &forward_pipeline_layout,
+            &vs_shader,
+            &fs_cutout_shader,
+            "chunk mesh cutout",
+            wgpu::BlendState::REPLACE,
+            true,
+        );
+        // translucent chunks are seen through, but don't hide each other
+        let translucent_pipeline = create_forward_pipeline(
+            renderer,
+            &forward_pipeline_layout,
+            &vs_shader,
+            &fs_shader,
+            "chunk mesh translucent",
+            wgpu::BlendState {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
             },
-            depth_stencil: renderer.depth_stencil().clone(),
-            multisample: wgpu::MultisampleState::default(),
-        });
+            false,
+        );
         let uv_buffer = device.create_buffer(&BufferDescriptor {
             label: Some("UvBuffer"),
             usage: wgpu::BufferUsage::STORAGE,
             size: 0,
             mapped_at_creation: false,
         });
+        let animation_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("AnimationBuffer"),
+            usage: wgpu::BufferUsage::STORAGE,
+            size: 0,
+            mapped_at_creation: false,
+        });
         Self {
             device,
             shadow_pipeline,
             forward_pipeline,
+            cutout_pipeline,
+            translucent_pipeline,
             bind_group_layout,
             diffuse_bind_group_layout,
             bind_group: None,
             uv_buffer,
+            animation_buffer,
             texture: None,
         }
     }
 }
+fn create_forward_pipeline(
+    renderer: &finger_paint_wgpu::WgpuRenderer,
+    layout: &wgpu::PipelineLayout,
+    vs_shader: &wgpu::ShaderModule,
+    fs_shader: &wgpu::ShaderModule,
+    name: &str,
+    blend: wgpu::BlendState,
+    depth_write_enabled: bool,
+) -> RenderPipeline {
+    let mut depth_stencil = renderer.depth_stencil().clone();
+    if let Some(depth_stencil) = &mut depth_stencil {
+        depth_stencil.depth_write_enabled = depth_write_enabled;
+    }
+    renderer
+        .device()
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("forward pipeline: {}", name)),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: vs_shader,
+                entry_point: "main",
+                buffers: &[ChunkVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: fs_shader,
+                entry_point: "main",
+                targets: &[wgpu::ColorTargetState {
+                    format: *renderer.format(),
+                    alpha_blend: blend.clone(),
+                    color_blend: blend,
+                    write_mask: wgpu::ColorWrite::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: wgpu::CullMode::Back,
+                ..Default::default()
+            },
+            depth_stencil,
+            multisample: wgpu::MultisampleState::default(),
+        })
+}
+
 impl ChunkMeshMiddleWare {
     pub fn load_atlas<P: AsRef<Path>>(&mut self, queue: &Queue, path: P) {
         self.texture = Some(
@@ -327,21 +480,32 @@ impl ChunkMeshMiddleWare {
             ],
         })
     }
-    pub fn load_chunk_mesh(&self, vertices: Vec<ChunkVertex>, position: Vector3<i32>) -> ChunkMesh {
+    pub fn load_chunk_mesh(
+        &self,
+        vertices: [Vec<ChunkVertex>; 3],
+        position: Vector3<i32>,
+    ) -> ChunkMesh {
         ChunkMesh::new(
             self.device.clone(),
             vertices,
             position,
             &self.bind_group_layout,
             &self.uv_buffer,
+            &self.animation_buffer,
         )
     }
-    pub fn load_uvs(&mut self, uvs: &[[f32; 2]]) {
+    /// `animations` has the frames, seconds per frame and frame height for every uv
+    pub fn load_uvs(&mut self, uvs: &[[f32; 2]], animations: &[[f32; 4]]) {
         self.uv_buffer = self.device.create_buffer_init(&BufferInitDescriptor {
             label: Some("UvBuffer"),
             contents: bytemuck::cast_slice(uvs),
             usage: wgpu::BufferUsage::STORAGE,
         });
+        self.animation_buffer = self.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("AnimationBuffer"),
+            contents: bytemuck::cast_slice(animations),
+            usage: wgpu::BufferUsage::STORAGE,
+        });
         self.bind_group = Some(Self::create_bind_group(
             &self.diffuse_bind_group_layout,
             self.texture.as_ref().unwrap(),
@@ -354,10 +518,15 @@ fn render_chunk_mesh<'a, 'b>(
     pass: &'b mut wgpu::RenderPass<'a>,
     chunk_mesh: &'a ChunkMesh,
     bgi: u32,
+    layer: RenderLayer,
 ) {
-    pass.set_vertex_buffer(0, chunk_mesh.vertex_buffer.slice(..));
+    let vertices = &chunk_mesh.vertices[layer as usize];
+    if vertices.is_empty() {
+        return;
+    }
+    pass.set_vertex_buffer(0, chunk_mesh.vertex_buffers[layer as usize].slice(..));
     pass.set_bind_group(bgi, &chunk_mesh.bind_group, &[]);
-    pass.draw(0..chunk_mesh.vertices.len() as u32, 0..1);
+    pass.draw(0..vertices.len() as u32, 0..1);
 }
 fn render_chunk_meshes<'a, 'b, 'c, U>(
     pipeline: &'a RenderPipeline,
@@ -365,6 +534,7 @@ fn render_chunk_meshes<'a, 'b, 'c, U>(
     bind_group: &'a BindGroup,
     mut chunk_meshes: U,
     bgi: u32,
+    layer: RenderLayer,
 ) where
     U: Iterator<Item = &'c ChunkMesh>,
     'c: 'a,
@@ -374,9 +544,9 @@ fn render_chunk_meshes<'a, 'b, 'c, U>(
         if bgi != 1 {
             pass.set_bind_group(1, bind_group, &[]);
         }
-        render_chunk_mesh(pass, first, bgi);
+        render_chunk_mesh(pass, first, bgi, layer);
         for chunk_mesh in chunk_meshes {
-            render_chunk_mesh(pass, chunk_mesh, bgi);
+            render_chunk_mesh(pass, chunk_mesh, bgi, layer);
         }
     }
 }
@@ -18,7 +18,7 @@ uniform ChunkUniforms {
 void main() {
     int z = (v & 0x000FC000) >> 14;
     int y = (v & 0x03F00000) >> 20;
-    int x = (v & 0xFC000000) >> 26;
+    int x = (v >> 26) & 0x0000003F;
 
-    gl_Position = view_proj * (vec4(ivec3(x, y, z) + position * chunk_size, 1.0));
+    gl_Position = view_proj * (vec4(vec3(ivec3(x, y, z)) * 0.5 + position * chunk_size, 1.0));
 }
@@ -8,27 +8,42 @@ use finger_paint_wgpu::wgpu::{VertexBufferLayout, VertexFormat};
 #[derive(Clone, Copy, Pod, Zeroable, Debug)]
 pub struct ChunkVertex {
     v: u32,
+    /// the color the texture is multiplied with, 8 bits for red, green and blue
+    tint: u32,
 }
 impl ChunkVertex {
     pub fn desc<'a>() -> VertexBufferLayout<'a> {
         VertexBufferLayout {
             array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
             step_mode: wgpu::InputStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute {
-                shader_location: 0,
-                offset: 0,
-                format: VertexFormat::Int,
-            }],
+            attributes: &[
+                wgpu::VertexAttribute {
+                    shader_location: 0,
+                    offset: 0,
+                    format: VertexFormat::Int,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 1,
+                    offset: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+                    format: VertexFormat::Int,
+                },
+            ],
         }
     }
+    /// `position` is in half blocks from the corner of the chunk, so it goes up to 32
     pub fn new(position: Vector3<u8>, normal: Dir, uv_index: u32) -> Self {
         (position, normal, uv_index).into()
     }
+    pub fn with_tint(mut self, [r, g, b]: [u8; 3]) -> Self {
+        self.tint = r as u32 | (g as u32) << 8 | (b as u32) << 16;
+        self
+    }
 }
 impl From<(Vector3<u8>, Dir, u32)> for ChunkVertex {
     fn from((position, normal, uv_index): (Vector3<u8>, Dir, u32)) -> Self {
         Self {
             v: pack(position, normal, uv_index),
+            tint: 0x00FF_FFFF,
         }
     }
 }
@@ -70,7 +85,7 @@ fn unpack(v: u32) -> (Vector3<u8>, Dir, u32) {
 fn vertex_packing() {
     let uv_index = 435;
     let dir = Dir::West;
-    let p = Vector3::new(9, 0, 16);
+    let p = Vector3::new(32, 0, 16);
     let v = pack(p, dir, uv_index);
     assert_eq!(unpack(v), (p, dir, uv_index));
 }
@@ -1,16 +1,22 @@
 #version 450
 
 layout(location=0) in int v;
+// the color grass and leaves are multiplied with, 8 bits for red, green and blue
+layout(location=1) in int tint;
 
 layout(location=0) out vec2 out_tex_coords;
 layout(location=1) out vec3 out_position;
 layout(location=2) out vec3 out_normal;
+layout(location=3) out vec3 out_tint;
 
 layout(set=0, binding=0)
 uniform Uniforms {
     mat4 view_proj;
     vec4 camera_pos;
     ivec4 num_lights;
+    vec4 ambient_color;
+    uint lighting_enabled;
+    float time;
 };
 
 layout(set=2, binding=0)
@@ -23,17 +29,24 @@ layout(set=2, binding=1) buffer TexCoords {
     vec2 tex_coords[];
 };
 
+// for every tex coord: the number of frames, the seconds per frame and the height of a frame
+layout(set=2, binding=2) buffer Animations {
+    vec4 animations[];
+};
+
 void main() {
     int tex_index =  v & 0x000007FF       ;
     int normal    = (v & 0x00003800) >> 11;
     int z         = (v & 0x000FC000) >> 14;
     int y         = (v & 0x03F00000) >> 20;
-    int x         = (v & 0xFC000000) >> 26;
+    int x         = (v >> 26) & 0x0000003F;
 
-    vec4 pos = (vec4(ivec3(x, y, z) + position * chunk_size, 1.0));
+    vec4 pos = (vec4(vec3(ivec3(x, y, z)) * 0.5 + position * chunk_size, 1.0));
     gl_Position = view_proj * pos;
     out_position = pos.xyz;
-    out_tex_coords = tex_coords[tex_index];
+    vec4 animation = animations[tex_index];
+    float frame = mod(floor(time / animation.y), animation.x);
+    out_tex_coords = tex_coords[tex_index] + vec2(0.0, frame * animation.z);
     if (normal == 0) {
         out_normal = vec3(0.0, 0.0, 1.0);
     } else if (normal == 1) {
@@ -47,4 +60,5 @@ void main() {
     } else if (normal == 5) {
         out_normal = vec3(0.0, -1.0, 0.0);
     }
+    out_tint = vec3(tint & 0xFF, (tint >> 8) & 0xFF, (tint >> 16) & 0xFF) / 255.0;
 }
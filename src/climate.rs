@@ -0,0 +1,101 @@
+use crate::chunk::CHUNK_SIZE;
+use crate::noise::fractal_2d;
+
+/// how big the warm and cold or wet and dry areas are in blocks
+const CLIMATE_SCALE: f32 = 160.0;
+/// how many columns in every direction the grass color is averaged over
+const BLEND_RADIUS: i32 = 3;
+
+/// the grass color in cold places, in hot and dry places and in hot and wet places
+const COLD: [f32; 3] = [96.0, 161.0, 123.0];
+const HOT_DRY: [f32; 3] = [191.0, 183.0, 85.0];
+const HOT_WET: [f32; 3] = [71.0, 205.0, 51.0];
+
+/// how warm and how wet a column of the world is, both from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Climate {
+    pub temperature: f32,
+    pub humidity: f32,
+}
+
+impl Default for Climate {
+    fn default() -> Self {
+        Self {
+            temperature: 0.5,
+            humidity: 0.5,
+        }
+    }
+}
+
+impl Climate {
+    /// the climate of the column at `x` `z` in world coordinates
+    pub fn at(seed: u64, x: i32, z: i32) -> Self {
+        let (x, z) = (x as f32, z as f32);
+        Self {
+            temperature: fractal_2d(seed, x, z, CLIMATE_SCALE, 3),
+            // another seed, so it isn't always wet where it's warm
+            humidity: fractal_2d(seed ^ 0x5EED, x, z, CLIMATE_SCALE, 3),
+        }
+    }
+    /// the color grass and leaves are tinted with
+    pub fn grass_color(self) -> [u8; 3] {
+        let mut color = [0; 3];
+        for (i, c) in color.iter_mut().enumerate() {
+            let dry = COLD[i] + (HOT_DRY[i] - COLD[i]) * self.temperature;
+            let wet = COLD[i] + (HOT_WET[i] - COLD[i]) * self.temperature;
+            *c = (dry + (wet - dry) * self.humidity).round() as u8;
+        }
+        color
+    }
+}
+
+/// the grass colors of a `CHUNK_SIZE` square of columns starting at `x` `z`, every one averaged with
+/// the columns around it, so it fades from one climate to the next instead of changing at once
+pub fn blended_grass_colors(seed: u64, x: i32, z: i32) -> [[[u8; 3]; CHUNK_SIZE]; CHUNK_SIZE] {
+    let size = CHUNK_SIZE as i32 + 2 * BLEND_RADIUS;
+    let mut colors = Vec::with_capacity((size * size) as usize);
+    for dx in 0..size {
+        for dz in 0..size {
+            let climate = Climate::at(seed, x + dx - BLEND_RADIUS, z + dz - BLEND_RADIUS);
+            colors.push(climate.grass_color());
+        }
+    }
+    let mut blended = [[[0; 3]; CHUNK_SIZE]; CHUNK_SIZE];
+    for (cx, row) in blended.iter_mut().enumerate() {
+        for (cz, color) in row.iter_mut().enumerate() {
+            let mut sum = [0u32; 3];
+            let mut count = 0;
+            for dx in 0..2 * BLEND_RADIUS + 1 {
+                for dz in 0..2 * BLEND_RADIUS + 1 {
+                    let other = colors[((cx as i32 + dx) * size + cz as i32 + dz) as usize];
+                    for (s, c) in sum.iter_mut().zip(other.iter()) {
+                        *s += *c as u32;
+                    }
+                    count += 1;
+                }
+            }
+            for (c, s) in color.iter_mut().zip(sum.iter()) {
+                *c = (*s / count) as u8;
+            }
+        }
+    }
+    blended
+}
+
+#[test]
+fn grass_color_fades_across_chunks() {
+    let left = blended_grass_colors(7, -16, 40);
+    let right = blended_grass_colors(7, 0, 40);
+    assert_eq!(right, blended_grass_colors(7, 0, 40));
+    for z in 0..CHUNK_SIZE {
+        let row: Vec<[u8; 3]> = (0..CHUNK_SIZE)
+            .map(|x| left[x][z])
+            .chain((0..CHUNK_SIZE).map(|x| right[x][z]))
+            .collect();
+        for pair in row.windows(2) {
+            for (a, b) in pair[0].iter().zip(pair[1].iter()) {
+                assert!((*a as i32 - *b as i32).abs() <= 2);
+            }
+        }
+    }
+}
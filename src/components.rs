@@ -1,4 +1,7 @@
+use crate::blocks::Block;
 use crate::dir::Dir;
+use crate::flat_middleware::Rect;
+use crate::items::Item;
 use crate::manager::{EcsModelHandle, EcsUvMesh};
 use cgmath::{Matrix3, Vector3};
 use specs::Component;
@@ -16,6 +19,33 @@ pub struct Rotation(pub Matrix3<f32>);
 #[derive(Component, Debug, Clone, Copy)]
 pub struct Velocity(pub Vector3<f32>);
 
+/// the space an entity takes up, relative to its Position, blocks can't be placed inside it
+#[derive(Component, Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+impl BoundingBox {
+    pub fn unit_cube() -> Self {
+        Self {
+            min: Vector3::new(0.0, 0.0, 0.0),
+            max: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+    /// whether the box at `position` overlaps the block at `coords`, touching doesn't count
+    pub fn intersects_block(&self, position: Vector3<f32>, coords: Vector3<i32>) -> bool {
+        let min = position + self.min;
+        let max = position + self.max;
+        let block = Vector3::new(coords.x as f32, coords.y as f32, coords.z as f32);
+        min.x < block.x + 1.0
+            && max.x > block.x
+            && min.y < block.y + 1.0
+            && max.y > block.y
+            && min.z < block.z + 1.0
+            && max.z > block.z
+    }
+}
+
 #[derive(Component)]
 pub struct Model(pub EcsModelHandle);
 
@@ -25,6 +55,10 @@ pub struct UvMesh(pub EcsUvMesh);
 #[derive(Component)]
 pub struct FlatMesh(pub crate::flat_middleware::FlatMesh);
 
+/// additional instances of a FlatMesh, for drawing the same mesh multiple times
+#[derive(Component, Debug, Default)]
+pub struct FlatInstances(pub Vec<Rect>);
+
 #[derive(Component, Debug)]
 pub struct Camera {
     pub fov: f32,
@@ -50,6 +84,14 @@ pub struct FirstPersonController {
 #[derive(Component, Debug)]
 pub struct Player;
 
+/// keeps the chunk columns closer than `radius` to the Position of the entity loaded,
+/// the chunks of tickets with a higher priority are generated first
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ChunkTicket {
+    pub radius: i32,
+    pub priority: i32,
+}
+
 #[derive(Component)]
 pub struct RealLight(pub finger_paint_wgpu::RLH);
 
@@ -68,6 +110,31 @@ pub struct LookingAtMarker {
 #[derive(Component, Debug)]
 pub struct BlockHighlightCube(pub Entity);
 
+/// how far a player got breaking the block at `coords`, from 0 to 1
+#[derive(Component, Debug)]
+pub struct BreakProgress {
+    pub coords: Vector3<i32>,
+    pub progress: f32,
+}
+
+/// shows the BreakProgress of the player as cracks on the block
+#[derive(Component, Debug)]
+pub struct CrackOverlay(pub Entity);
+
+/// draws the icon of an item in every hotbar slot that contains it
+#[derive(Component, Debug)]
+pub struct HotbarIcon(pub Item);
+
+/// the frames around the hotbar slots, either the selected one or all the others
+#[derive(Component, Debug)]
+pub struct HotbarFrame {
+    pub selected: bool,
+}
+
+/// a block that lost its support and falls until it lands and turns back into a block
+#[derive(Component, Debug)]
+pub struct FallingBlock(pub Block);
+
 /// stores the Player so the Sun and more importantly its shadow can follow the Player
 #[derive(Component, Debug)]
 pub struct Sun {
@@ -75,3 +142,19 @@ pub struct Sun {
     pub size: f32,
     pub distance: f32,
 }
+
+#[test]
+fn bounding_box_intersects_block() {
+    let bounding_box = BoundingBox {
+        min: Vector3::new(-0.3, -1.5, -0.3),
+        max: Vector3::new(0.3, 0.2, 0.3),
+    };
+    let position = Vector3::new(0.5, 1.6, 0.5);
+    assert!(bounding_box.intersects_block(position, Vector3::new(0, 0, 0)));
+    assert!(bounding_box.intersects_block(position, Vector3::new(0, 1, 0)));
+    assert!(!bounding_box.intersects_block(position, Vector3::new(0, 2, 0)));
+    assert!(!bounding_box.intersects_block(position, Vector3::new(1, 1, 0)));
+    // touching the top of the block below is fine
+    assert!(!BoundingBox::unit_cube()
+        .intersects_block(Vector3::new(0.0, 1.0, 0.0), Vector3::new(0, 0, 0)));
+}
@@ -0,0 +1,423 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::items::{Item, ItemStack};
+
+/// how a recipe is written in recipes.json
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RecipeDefinition {
+    Shaped {
+        pattern: Vec<String>,
+        key: HashMap<char, String>,
+        result: String,
+        #[serde(default = "one")]
+        count: u32,
+    },
+    Shapeless {
+        ingredients: Vec<String>,
+        result: String,
+        #[serde(default = "one")]
+        count: u32,
+    },
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Debug)]
+pub enum RecipeError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    UnknownItem(String),
+    /// a character of a pattern that is not in the key of the recipe
+    UnknownKey(char),
+    /// the rows of a pattern don't all have the same length
+    RaggedPattern,
+    EmptyRecipe,
+}
+
+impl std::fmt::Display for RecipeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecipeError::Io(error) => write!(f, "{}", error),
+            RecipeError::Json(error) => write!(f, "invalid recipe: {}", error),
+            RecipeError::UnknownItem(name) => write!(f, "unknown item \"{}\"", name),
+            RecipeError::UnknownKey(c) => write!(f, "'{}' is not in the key of the pattern", c),
+            RecipeError::RaggedPattern => write!(f, "pattern rows have different lengths"),
+            RecipeError::EmptyRecipe => write!(f, "recipe without ingredients"),
+        }
+    }
+}
+
+impl From<std::io::Error> for RecipeError {
+    fn from(error: std::io::Error) -> Self {
+        RecipeError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for RecipeError {
+    fn from(error: serde_json::Error) -> Self {
+        RecipeError::Json(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecipeShape {
+    /// the items have to be in this arrangement, or its mirror image, anywhere in the grid
+    Shaped {
+        width: usize,
+        height: usize,
+        cells: Vec<Option<Item>>,
+    },
+    /// the items can be anywhere in the grid
+    Shapeless(Vec<Item>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    pub shape: RecipeShape,
+    pub result: ItemStack,
+}
+
+fn item_from_name(name: &str) -> Result<Item, RecipeError> {
+    Item::from_name(name).ok_or_else(|| RecipeError::UnknownItem(name.into()))
+}
+
+impl Recipe {
+    fn from_definition(definition: RecipeDefinition) -> Result<Self, RecipeError> {
+        match definition {
+            RecipeDefinition::Shaped {
+                pattern,
+                key,
+                result,
+                count,
+            } => {
+                let width = pattern.first().map_or(0, |row| row.chars().count());
+                if pattern.iter().any(|row| row.chars().count() != width) {
+                    return Err(RecipeError::RaggedPattern);
+                }
+                let mut cells = Vec::with_capacity(width * pattern.len());
+                for c in pattern.iter().flat_map(|row| row.chars()) {
+                    cells.push(match c {
+                        ' ' => None,
+                        c => {
+                            let name = key.get(&c).ok_or(RecipeError::UnknownKey(c))?;
+                            Some(item_from_name(name)?)
+                        }
+                    });
+                }
+                let shape =
+                    trim_pattern(width, pattern.len(), &cells).ok_or(RecipeError::EmptyRecipe)?;
+                Ok(Self {
+                    shape,
+                    result: ItemStack::new(item_from_name(&result)?, count),
+                })
+            }
+            RecipeDefinition::Shapeless {
+                ingredients,
+                result,
+                count,
+            } => {
+                if ingredients.is_empty() {
+                    return Err(RecipeError::EmptyRecipe);
+                }
+                let ingredients = ingredients
+                    .iter()
+                    .map(|name| item_from_name(name))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Self {
+                    shape: RecipeShape::Shapeless(ingredients),
+                    result: ItemStack::new(item_from_name(&result)?, count),
+                })
+            }
+        }
+    }
+    pub fn matches(&self, grid: &CraftingGrid) -> bool {
+        match &self.shape {
+            RecipeShape::Shaped {
+                width,
+                height,
+                cells,
+            } => {
+                let (x0, y0, x1, y1) = match grid.bounds() {
+                    Some(bounds) => bounds,
+                    None => return false,
+                };
+                if x1 - x0 != *width || y1 - y0 != *height {
+                    return false;
+                }
+                let matches_with = |mirrored: bool| {
+                    (0..*height).all(|y| {
+                        (0..*width).all(|x| {
+                            let px = if mirrored { width - 1 - x } else { x };
+                            cells[y * width + px] == grid.get(x0 + x, y0 + y).map(|s| s.item)
+                        })
+                    })
+                };
+                matches_with(false) || matches_with(true)
+            }
+            RecipeShape::Shapeless(ingredients) => {
+                let mut missing = ingredients.clone();
+                for stack in grid.slots.iter().flatten() {
+                    match missing.iter().position(|item| *item == stack.item) {
+                        Some(i) => {
+                            missing.swap_remove(i);
+                        }
+                        None => return false,
+                    }
+                }
+                missing.is_empty()
+            }
+        }
+    }
+}
+
+/// cuts off the empty rows and columns around a pattern, `None` if it is completely empty
+fn trim_pattern(width: usize, height: usize, cells: &[Option<Item>]) -> Option<RecipeShape> {
+    let occupied = |x: usize, y: usize| cells[y * width + x].is_some();
+    let columns: Vec<usize> = (0..width)
+        .filter(|x| (0..height).any(|y| occupied(*x, y)))
+        .collect();
+    let rows: Vec<usize> = (0..height)
+        .filter(|y| (0..width).any(|x| occupied(x, *y)))
+        .collect();
+    let (x0, x1) = (*columns.first()?, *columns.last()? + 1);
+    let (y0, y1) = (*rows.first()?, *rows.last()? + 1);
+    let mut trimmed = Vec::with_capacity((x1 - x0) * (y1 - y0));
+    for y in y0..y1 {
+        trimmed.extend_from_slice(&cells[y * width + x0..y * width + x1]);
+    }
+    Some(RecipeShape::Shaped {
+        width: x1 - x0,
+        height: y1 - y0,
+        cells: trimmed,
+    })
+}
+
+/// the items a player put into a crafting grid, row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CraftingGrid {
+    pub width: usize,
+    pub height: usize,
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl CraftingGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            slots: vec![None; width * height],
+        }
+    }
+    pub fn get(&self, x: usize, y: usize) -> Option<ItemStack> {
+        self.slots[y * self.width + x]
+    }
+    pub fn set(&mut self, x: usize, y: usize, stack: Option<ItemStack>) {
+        self.slots[y * self.width + x] = stack;
+    }
+    /// the smallest rectangle containing all occupied slots as (x0, y0, x1, y1), exclusive at the end
+    fn bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y).is_some() {
+                    bounds = Some(match bounds {
+                        Some((x0, y0, x1, y1)) => {
+                            (x0.min(x), y0.min(y), x1.max(x + 1), y1.max(y + 1))
+                        }
+                        None => (x, y, x + 1, y + 1),
+                    });
+                }
+            }
+        }
+        bounds
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Recipes {
+    recipes: Vec<Recipe>,
+}
+
+impl Recipes {
+    pub fn load(path: &Path) -> Result<Self, RecipeError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+    pub fn from_json(json: &str) -> Result<Self, RecipeError> {
+        let definitions: Vec<RecipeDefinition> = serde_json::from_str(json)?;
+        let recipes = definitions
+            .into_iter()
+            .map(Recipe::from_definition)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { recipes })
+    }
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+    /// the first recipe matching the grid, in the order they are defined
+    pub fn find(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| recipe.matches(grid))
+    }
+    /// crafts once, using up one item of every occupied slot, and returns the result
+    pub fn craft(&self, grid: &mut CraftingGrid) -> Option<ItemStack> {
+        let result = self.find(grid)?.result;
+        for slot in &mut grid.slots {
+            if let Some(stack) = slot {
+                stack.count -= 1;
+                if stack.count == 0 {
+                    *slot = None;
+                }
+            }
+        }
+        Some(result)
+    }
+}
+
+use crate::blocks::Block;
+
+const TEST_RECIPES: &str = r#"[
+    {
+        "type": "shaped",
+        "pattern": ["SSS", " S ", " S "],
+        "key": { "S": "stone" },
+        "result": "stone_pickaxe"
+    },
+    {
+        "type": "shaped",
+        "pattern": ["SD", "D "],
+        "key": { "S": "stone", "D": "dirt" },
+        "result": "sand",
+        "count": 4
+    },
+    {
+        "type": "shapeless",
+        "ingredients": ["dirt", "water"],
+        "result": "grass"
+    }
+]"#;
+
+fn stack(block: Block, count: u32) -> Option<ItemStack> {
+    Some(ItemStack::new(Item::Block(block), count))
+}
+
+#[test]
+fn shaped_recipe_matches_anywhere_in_grid() {
+    let recipes = Recipes::from_json(TEST_RECIPES).unwrap();
+    let mut grid = CraftingGrid::new(3, 3);
+    grid.set(1, 1, stack(Block::Stone, 1));
+    grid.set(2, 1, stack(Block::Dirt, 1));
+    grid.set(1, 2, stack(Block::Dirt, 1));
+    assert_eq!(
+        recipes.find(&grid).map(|r| r.result),
+        Some(ItemStack::new(Item::Block(Block::Sand), 4))
+    );
+    // an extra item anywhere breaks the shape
+    grid.set(0, 0, stack(Block::Dirt, 1));
+    assert_eq!(recipes.find(&grid), None);
+}
+#[test]
+fn shaped_recipe_matches_mirrored() {
+    let recipes = Recipes::from_json(TEST_RECIPES).unwrap();
+    let mut grid = CraftingGrid::new(3, 3);
+    grid.set(1, 0, stack(Block::Stone, 1));
+    grid.set(0, 0, stack(Block::Dirt, 1));
+    grid.set(1, 1, stack(Block::Dirt, 1));
+    assert_eq!(
+        recipes.find(&grid).map(|r| r.result.item),
+        Some(Item::Block(Block::Sand))
+    );
+    // flipped upside down is not the same recipe
+    let mut grid = CraftingGrid::new(3, 3);
+    grid.set(0, 1, stack(Block::Stone, 1));
+    grid.set(1, 1, stack(Block::Dirt, 1));
+    grid.set(0, 0, stack(Block::Dirt, 1));
+    assert_eq!(recipes.find(&grid), None);
+}
+#[test]
+fn shaped_recipe_needs_whole_pattern() {
+    let recipes = Recipes::from_json(TEST_RECIPES).unwrap();
+    let mut grid = CraftingGrid::new(3, 3);
+    for x in 0..3 {
+        grid.set(x, 0, stack(Block::Stone, 1));
+    }
+    grid.set(1, 1, stack(Block::Stone, 1));
+    assert_eq!(recipes.find(&grid), None);
+    grid.set(1, 2, stack(Block::Stone, 1));
+    assert_eq!(
+        recipes.find(&grid).map(|r| r.result.item),
+        Some(Item::StonePickaxe)
+    );
+    grid.set(1, 2, stack(Block::Dirt, 1));
+    assert_eq!(recipes.find(&grid), None);
+}
+#[test]
+fn shapeless_recipe_ignores_positions() {
+    let recipes = Recipes::from_json(TEST_RECIPES).unwrap();
+    let mut grid = CraftingGrid::new(2, 2);
+    grid.set(1, 1, stack(Block::Water, 1));
+    grid.set(0, 0, stack(Block::Dirt, 1));
+    assert_eq!(
+        recipes.find(&grid).map(|r| r.result.item),
+        Some(Item::Block(Block::Grass))
+    );
+    grid.set(1, 0, stack(Block::Dirt, 1));
+    assert_eq!(recipes.find(&grid), None);
+    grid.set(1, 0, None);
+    grid.set(1, 1, None);
+    assert_eq!(recipes.find(&grid), None);
+}
+#[test]
+fn crafting_uses_one_item_per_slot() {
+    let recipes = Recipes::from_json(TEST_RECIPES).unwrap();
+    let mut grid = CraftingGrid::new(3, 3);
+    grid.set(0, 0, stack(Block::Dirt, 2));
+    grid.set(2, 2, stack(Block::Water, 1));
+    assert_eq!(recipes.craft(&mut grid), stack(Block::Grass, 1));
+    assert_eq!(grid.get(0, 0), stack(Block::Dirt, 1));
+    assert_eq!(grid.get(2, 2), None);
+    // the water is used up, so there is nothing to craft anymore and nothing is consumed
+    assert_eq!(recipes.craft(&mut grid), None);
+    assert_eq!(grid.get(0, 0), stack(Block::Dirt, 1));
+}
+#[test]
+fn invalid_recipes_are_rejected() {
+    assert!(matches!(
+        Recipes::from_json(r#"[{"type": "shapeless", "ingredients": ["diamond"], "result": "dirt"}]"#),
+        Err(RecipeError::UnknownItem(name)) if name == "diamond"
+    ));
+    assert!(matches!(
+        Recipes::from_json(
+            r#"[{"type": "shaped", "pattern": ["SX"], "key": {"S": "stone"}, "result": "dirt"}]"#
+        ),
+        Err(RecipeError::UnknownKey('X'))
+    ));
+    assert!(matches!(
+        Recipes::from_json(
+            r#"[{"type": "shaped", "pattern": ["SS", "S"], "key": {"S": "stone"}, "result": "dirt"}]"#
+        ),
+        Err(RecipeError::RaggedPattern)
+    ));
+    assert!(matches!(
+        Recipes::from_json(
+            r#"[{"type": "shaped", "pattern": ["  "], "key": {}, "result": "dirt"}]"#
+        ),
+        Err(RecipeError::EmptyRecipe)
+    ));
+    assert!(matches!(
+        Recipes::from_json(r#"[{"type": "smelting"}]"#),
+        Err(RecipeError::Json(_))
+    ));
+}
+#[test]
+fn bundled_recipes_load() {
+    let recipes = Recipes::load(Path::new("recipes.json")).unwrap();
+    assert!(!recipes.is_empty());
+    assert!(matches!(
+        Recipes::load(Path::new("missing.json")),
+        Err(RecipeError::Io(_))
+    ));
+}
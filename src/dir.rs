@@ -19,6 +19,35 @@ impl Dir {
     pub fn iter() -> impl Iterator<Item = Dir> {
         DirIter::new()
     }
+    pub fn opposite(self) -> Dir {
+        match self {
+            Dir::North => Dir::South,
+            Dir::South => Dir::North,
+            Dir::East => Dir::West,
+            Dir::West => Dir::East,
+            Dir::Up => Dir::Down,
+            Dir::Down => Dir::Up,
+        }
+    }
+    /// the horizontal direction closest to where a yaw of a FirstPersonController points
+    pub fn from_yaw(yaw: f32) -> Dir {
+        let (x, z) = (yaw.sin(), yaw.cos());
+        if x.abs() > z.abs() {
+            if x > 0.0 {
+                Dir::East
+            } else {
+                Dir::West
+            }
+        } else if z > 0.0 {
+            Dir::North
+        } else {
+            Dir::South
+        }
+    }
+    /// the direction of a unit vector along one of the axes
+    pub fn from_vector(v: Vector3<i32>) -> Option<Dir> {
+        Dir::iter().find(|dir| Vector3::from(*dir) == v)
+    }
 }
 impl From<Dir> for Vector3<i32> {
     fn from(dir: Dir) -> Self {
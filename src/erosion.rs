@@ -0,0 +1,291 @@
+use crate::biomes::biome_at;
+use crate::chunk::CHUNK_SIZE;
+use crate::noise::hash;
+use crate::world_gen::{
+    blended_height, height_to_blocks, surface_height, GeneratorSettings, HEIGHT_BLEND_RADIUS,
+};
+use cgmath::Vector3;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// how many columns a side of a region is, erosion is simulated for a whole region at once
+const REGION_SIZE: i32 = 64;
+/// how far the simulation of a region reaches into the regions around it,
+/// where two regions overlap their erosion is faded from one to the other
+const REGION_MARGIN: i32 = 16;
+/// how many columns a side of the simulated heightmap is
+const TILE_SIZE: i32 = REGION_SIZE + 2 * REGION_MARGIN;
+/// how many chunk columns from the middle of a region its heights can be looked up from, with some to spare
+const REGION_REACH: i32 = (REGION_SIZE + REGION_MARGIN) / CHUNK_SIZE as i32 + 2;
+
+const EROSION_SEED: u64 = 0xE205;
+/// how many raindrops run down every column of a region
+const DROPS_PER_COLUMN: f32 = 1.5;
+/// how many steps a drop makes before it dries up
+const DROP_LIFETIME: u32 = 40;
+/// how much a drop keeps going the way it went instead of downhill
+const INERTIA: f32 = 0.05;
+/// how much sediment a drop can carry for its speed, water and slope
+const SEDIMENT_CAPACITY: f32 = 0.5;
+const MIN_SEDIMENT_CAPACITY: f32 = 0.01;
+/// how much of the missing sediment a drop takes from the ground in one step
+const ERODE_SPEED: f32 = 0.3;
+/// how much of the sediment over its capacity a drop leaves on the ground in one step
+const DEPOSIT_SPEED: f32 = 0.3;
+/// how many columns around a drop it wears away, so it carves beds instead of holes
+const EROSION_RADIUS: i32 = 2;
+const EVAPORATE_SPEED: f32 = 0.02;
+const GRAVITY: f32 = 4.0;
+
+/// a simulated region and its margin
+struct Tile {
+    /// the height of every column before erosion, by x and z
+    heights: Vec<f32>,
+    /// how much erosion took away or put onto every column
+    changes: Vec<f32>,
+}
+
+impl Tile {
+    fn index(x: i32, z: i32) -> usize {
+        (x * TILE_SIZE + z) as usize
+    }
+    /// rains onto the region at `region` in region coordinates, the seed and the region decide where every drop falls
+    fn simulate(seed: u64, region: (i32, i32)) -> Self {
+        let start_x = region.0 * REGION_SIZE - REGION_MARGIN;
+        let start_z = region.1 * REGION_SIZE - REGION_MARGIN;
+        // the height profiles of the biomes of the tile and the columns around it, which are blended in
+        let padded = TILE_SIZE + 2 * HEIGHT_BLEND_RADIUS;
+        let mut profiles = Vec::with_capacity((padded * padded) as usize);
+        for x in 0..padded {
+            for z in 0..padded {
+                let x = start_x + x - HEIGHT_BLEND_RADIUS;
+                let z = start_z + z - HEIGHT_BLEND_RADIUS;
+                profiles.push(biome_at(seed, x, z).height());
+            }
+        }
+        let mut heights = Vec::with_capacity((TILE_SIZE * TILE_SIZE) as usize);
+        for x in 0..TILE_SIZE {
+            for z in 0..TILE_SIZE {
+                let profile = |px: i32, pz: i32| {
+                    let px = px - start_x + HEIGHT_BLEND_RADIUS;
+                    let pz = pz - start_z + HEIGHT_BLEND_RADIUS;
+                    profiles[(px * padded + pz) as usize]
+                };
+                heights.push(blended_height(seed, start_x + x, start_z + z, profile));
+            }
+        }
+        let mut eroded = heights.clone();
+        let mut rng = StdRng::seed_from_u64(hash(seed ^ EROSION_SEED, region.0, 0, region.1));
+        let drops = (DROPS_PER_COLUMN * (TILE_SIZE * TILE_SIZE) as f32) as u32;
+        for _ in 0..drops {
+            let x = rng.gen_range(0.0, (TILE_SIZE - 1) as f32);
+            let z = rng.gen_range(0.0, (TILE_SIZE - 1) as f32);
+            rain(&mut eroded, x, z);
+        }
+        let changes = eroded
+            .iter()
+            .zip(heights.iter())
+            .map(|(e, h)| e - h)
+            .collect();
+        Self { heights, changes }
+    }
+}
+
+/// the height and the slope at a point between the columns
+fn height_and_gradient(heights: &[f32], x: f32, z: f32) -> (f32, f32, f32) {
+    let (cx, cz) = (x.floor() as i32, z.floor() as i32);
+    let (u, v) = (x - cx as f32, z - cz as f32);
+    let h00 = heights[Tile::index(cx, cz)];
+    let h10 = heights[Tile::index(cx + 1, cz)];
+    let h01 = heights[Tile::index(cx, cz + 1)];
+    let h11 = heights[Tile::index(cx + 1, cz + 1)];
+    let gradient_x = (h10 - h00) * (1.0 - v) + (h11 - h01) * v;
+    let gradient_z = (h01 - h00) * (1.0 - u) + (h11 - h10) * u;
+    let height =
+        h00 * (1.0 - u) * (1.0 - v) + h10 * u * (1.0 - v) + h01 * (1.0 - u) * v + h11 * u * v;
+    (height, gradient_x, gradient_z)
+}
+
+/// adds `amount` to the ground around a point, split between the four columns around it
+fn add_to_ground(heights: &mut [f32], x: f32, z: f32, amount: f32) {
+    let (cx, cz) = (x.floor() as i32, z.floor() as i32);
+    let (u, v) = (x - cx as f32, z - cz as f32);
+    heights[Tile::index(cx, cz)] += amount * (1.0 - u) * (1.0 - v);
+    heights[Tile::index(cx + 1, cz)] += amount * u * (1.0 - v);
+    heights[Tile::index(cx, cz + 1)] += amount * (1.0 - u) * v;
+    heights[Tile::index(cx + 1, cz + 1)] += amount * u * v;
+}
+
+/// takes `amount` away from the ground around a point, most of it from close by
+fn wear_away(heights: &mut [f32], x: f32, z: f32, amount: f32) {
+    let (cx, cz) = (x.round() as i32, z.round() as i32);
+    let mut brush = Vec::new();
+    let mut total = 0.0;
+    for bx in cx - EROSION_RADIUS..cx + EROSION_RADIUS + 1 {
+        for bz in cz - EROSION_RADIUS..cz + EROSION_RADIUS + 1 {
+            let inside = |c: i32| (0..TILE_SIZE).contains(&c);
+            let distance = ((bx as f32 - x).powi(2) + (bz as f32 - z).powi(2)).sqrt();
+            let weight = EROSION_RADIUS as f32 - distance;
+            if inside(bx) && inside(bz) && weight > 0.0 {
+                brush.push((Tile::index(bx, bz), weight));
+                total += weight;
+            }
+        }
+    }
+    for (index, weight) in brush {
+        heights[index] -= amount * weight / total;
+    }
+}
+
+/// lets a drop of water run down from `x` `z`,
+/// it takes ground with it where it speeds up and leaves it where it slows down,
+/// so many drops carve valleys and river beds
+fn rain(heights: &mut [f32], mut x: f32, mut z: f32) {
+    let (mut direction_x, mut direction_z) = (0.0, 0.0);
+    let mut speed = 1.0;
+    let mut water = 1.0;
+    let mut sediment = 0.0;
+    for _ in 0..DROP_LIFETIME {
+        let (height, gradient_x, gradient_z) = height_and_gradient(heights, x, z);
+        direction_x = direction_x * INERTIA - gradient_x * (1.0 - INERTIA);
+        direction_z = direction_z * INERTIA - gradient_z * (1.0 - INERTIA);
+        let length: f32 = (direction_x * direction_x + direction_z * direction_z).sqrt();
+        if length < 1e-6 {
+            break;
+        }
+        direction_x /= length;
+        direction_z /= length;
+        let (old_x, old_z) = (x, z);
+        x += direction_x;
+        z += direction_z;
+        let outside = |c: f32| c < 0.0 || c >= (TILE_SIZE - 1) as f32;
+        if outside(x) || outside(z) {
+            break;
+        }
+        let (new_height, _, _) = height_and_gradient(heights, x, z);
+        let fall = new_height - height;
+        let capacity = (-fall * speed * water * SEDIMENT_CAPACITY).max(MIN_SEDIMENT_CAPACITY);
+        if fall > 0.0 || sediment > capacity {
+            // uphill the drop fills the pit behind it, otherwise it drops what it can't carry
+            let amount = if fall > 0.0 {
+                fall.min(sediment)
+            } else {
+                (sediment - capacity) * DEPOSIT_SPEED
+            };
+            sediment -= amount;
+            add_to_ground(heights, old_x, old_z, amount);
+        } else {
+            let amount = ((capacity - sediment) * ERODE_SPEED).min(-fall);
+            sediment += amount;
+            wear_away(heights, old_x, old_z, amount);
+        }
+        speed = (speed * speed - fall * GRAVITY).max(0.0).sqrt();
+        water *= 1.0 - EVAPORATE_SPEED;
+    }
+}
+
+/// how much of the erosion of a region counts at `offset` columns from its start along one axis,
+/// it fades out over the margins, where the neighbouring region fades in
+fn fade(offset: i32) -> f32 {
+    let fade_in = ((offset + REGION_MARGIN) as f32 + 0.5) / (2 * REGION_MARGIN) as f32;
+    let fade_out =
+        ((REGION_SIZE + REGION_MARGIN - offset) as f32 - 0.5) / (2 * REGION_MARGIN) as f32;
+    fade_in.min(fade_out).clamp(0.0, 1.0)
+}
+
+/// the heights of the ground for generation, with the eroded regions kept around so every region is only simulated once
+#[derive(Default)]
+pub struct HeightCache {
+    tiles: HashMap<(i32, i32), Tile>,
+}
+
+impl HeightCache {
+    fn tile(&mut self, seed: u64, region: (i32, i32)) -> &Tile {
+        self.tiles
+            .entry(region)
+            .or_insert_with(|| Tile::simulate(seed, region))
+    }
+    /// forgets the regions no chunk is generated near anymore, `needed` gets the chunk column in the middle
+    /// of a region and how many chunk columns around it the region is used for
+    pub fn forget_regions(&mut self, needed: impl Fn(Vector3<i32>, i32) -> bool) {
+        let middle =
+            |region: i32| (region * REGION_SIZE + REGION_SIZE / 2).div_euclid(CHUNK_SIZE as i32);
+        self.tiles
+            .retain(|&(x, z), _| needed(Vector3::new(middle(x), 0, middle(z)), REGION_REACH));
+    }
+    /// how many blocks of ground the column at `x` `z` has
+    pub fn height(&mut self, seed: u64, settings: &GeneratorSettings, x: i32, z: i32) -> i32 {
+        if !settings.erosion {
+            return surface_height(seed, x, z);
+        }
+        let region_x = x.div_euclid(REGION_SIZE);
+        let region_z = z.div_euclid(REGION_SIZE);
+        let mut height = None;
+        let mut change = 0.0;
+        // the region of the column and the neighbours whose margins reach over it
+        for rx in region_x - 1..region_x + 2 {
+            for rz in region_z - 1..region_z + 2 {
+                let (offset_x, offset_z) = (x - rx * REGION_SIZE, z - rz * REGION_SIZE);
+                let weight = fade(offset_x) * fade(offset_z);
+                if weight <= 0.0 {
+                    continue;
+                }
+                let tile = self.tile(seed, (rx, rz));
+                let index = Tile::index(offset_x + REGION_MARGIN, offset_z + REGION_MARGIN);
+                height = Some(tile.heights[index]);
+                change += tile.changes[index] * weight;
+            }
+        }
+        // every region has the same height before erosion for a column
+        height_to_blocks(height.unwrap() + change)
+    }
+}
+
+#[test]
+fn erosion_is_deterministic_and_without_seams() {
+    let seed = 8;
+    let settings = GeneratorSettings::default();
+    let mut forwards = HeightCache::default();
+    let mut backwards = HeightCache::default();
+    let columns: Vec<(i32, i32)> = (-80..80).map(|x| (x, 3 * x / 4 + 5)).collect();
+    let heights: Vec<i32> = columns
+        .iter()
+        .map(|&(x, z)| forwards.height(seed, &settings, x, z))
+        .collect();
+    // the regions are simulated in another order, but give the same heights
+    for (&(x, z), height) in columns.iter().zip(heights.iter()).rev() {
+        assert_eq!(backwards.height(seed, &settings, x, z), *height);
+    }
+    let changed = columns
+        .iter()
+        .zip(heights.iter())
+        .filter(|(&(x, z), height)| **height != surface_height(seed, x, z))
+        .count();
+    assert!(changed > 0);
+    // forgotten regions are simulated again the same way
+    forwards.forget_regions(|column, _| column.x < 0);
+    assert!(forwards.tiles.keys().all(|&(x, _)| x < 0));
+    backwards.forget_regions(|_, _| false);
+    assert!(backwards.tiles.is_empty());
+    for (&(x, z), height) in columns.iter().zip(heights.iter()) {
+        assert_eq!(forwards.height(seed, &settings, x, z), *height);
+        assert_eq!(backwards.height(seed, &settings, x, z), *height);
+    }
+    // the ground doesn't jump where one region ends and the next one begins
+    for &border in &[0, REGION_SIZE] {
+        for along in -REGION_SIZE..REGION_SIZE {
+            let mut height = |x, z| forwards.height(seed, &settings, x, z);
+            assert!((height(border - 1, along) - height(border, along)).abs() <= 3);
+            assert!((height(along, border - 1) - height(along, border)).abs() <= 3);
+        }
+    }
+    // without erosion the heights are the plain ones
+    let plain = GeneratorSettings {
+        erosion: false,
+        ..settings
+    };
+    assert_eq!(
+        forwards.height(seed, &plain, 3, 4),
+        surface_height(seed, 3, 4)
+    );
+}
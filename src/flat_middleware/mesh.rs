@@ -62,6 +62,18 @@ pub struct FlatMesh {
 
 impl FlatMesh {
     pub fn new<P: AsRef<Path>>(device: Arc<Device>, queue: &Queue, path: P) -> Self {
+        let texture = Texture::load(
+            &device,
+            queue,
+            path,
+            FilterMode::Nearest,
+            FilterMode::Nearest,
+            false,
+        )
+        .unwrap();
+        Self::from_texture(device, texture)
+    }
+    pub fn from_texture(device: Arc<Device>, texture: Texture) -> Self {
         let vertex_buffer = Self::create_vertex_buffer(
             &device,
             &[
@@ -73,15 +85,6 @@ impl FlatMesh {
                 FlatVertex::new(Vector2::new(1.0, 1.0), Vector2::new(1.0, 1.0)),
             ],
         );
-        let texture = Texture::load(
-            &device,
-            queue,
-            path,
-            FilterMode::Nearest,
-            FilterMode::Nearest,
-            false,
-        )
-        .unwrap();
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("diffuse bind group"),
             layout: &ChunkMesh::diffuse_bind_group_layout(&device),
@@ -241,6 +244,19 @@ impl FlatMiddleWare {
     pub fn load_flat_mesh<P: AsRef<Path>>(&mut self, path: P) -> FlatMesh {
         FlatMesh::new(self.device.clone(), &self.queue, path)
     }
+    /// creates a flat mesh from raw rgba8 pixels, the first row is the bottom of the mesh
+    pub fn load_flat_mesh_from_raw(&mut self, size: (u32, u32), data: &[u8]) -> FlatMesh {
+        let texture = Texture::from_raw(
+            &self.device,
+            &self.queue,
+            size,
+            data,
+            FilterMode::Nearest,
+            FilterMode::Nearest,
+            false,
+        );
+        FlatMesh::from_texture(self.device.clone(), texture)
+    }
 }
 
 fn render_flat_mesh<'a, 'b>(pass: &'b mut wgpu::RenderPass<'a>, flat_mesh: &'a FlatMesh) {
@@ -0,0 +1,107 @@
+use crate::blocks::Block;
+use crate::dir::Dir;
+use cgmath::Vector3;
+use std::collections::VecDeque;
+
+/// how many block edits the history remembers at most, the oldest operations are forgotten first
+pub const MAX_HISTORY_EDITS: usize = 100_000;
+
+/// one block that was changed, in world coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockEdit {
+    pub coords: Vector3<i32>,
+    pub old: (Block, Dir),
+    pub new: (Block, Dir),
+}
+
+/// the block edits of the players, grouped into the operations that made them, so they can be undone and redone
+#[derive(Debug, Default)]
+pub struct EditHistory {
+    undo: VecDeque<Vec<BlockEdit>>,
+    redo: Vec<Vec<BlockEdit>>,
+    /// how many edits are in `undo` and `redo` together
+    edits: usize,
+}
+
+impl EditHistory {
+    /// remembers an operation, what was undone before can't be redone anymore
+    pub fn record(&mut self, operation: Vec<BlockEdit>) {
+        if operation.is_empty() {
+            return;
+        }
+        for undone in self.redo.drain(..) {
+            self.edits -= undone.len();
+        }
+        if operation.len() > MAX_HISTORY_EDITS {
+            // the operations before it can't be undone without undoing it first
+            self.undo.clear();
+            self.edits = 0;
+            return;
+        }
+        while self.edits + operation.len() > MAX_HISTORY_EDITS {
+            let oldest = self.undo.pop_front().unwrap();
+            self.edits -= oldest.len();
+        }
+        self.edits += operation.len();
+        self.undo.push_back(operation);
+    }
+    /// the blocks to set to undo the last operation, as coordinates, block and facing
+    pub fn undo(&mut self) -> Option<Vec<(Vector3<i32>, Block, Dir)>> {
+        let operation = self.undo.pop_back()?;
+        // the last edit of a block is undone first, so the block ends up like before the first
+        let changes = operation
+            .iter()
+            .rev()
+            .map(|edit| (edit.coords, edit.old.0, edit.old.1))
+            .collect();
+        self.redo.push(operation);
+        Some(changes)
+    }
+    /// the blocks to set to redo the last undone operation
+    pub fn redo(&mut self) -> Option<Vec<(Vector3<i32>, Block, Dir)>> {
+        let operation = self.redo.pop()?;
+        let changes = operation
+            .iter()
+            .map(|edit| (edit.coords, edit.new.0, edit.new.1))
+            .collect();
+        self.undo.push_back(operation);
+        Some(changes)
+    }
+}
+
+#[test]
+fn undo_and_redo_walk_the_history() {
+    let edit = |x, old, new| BlockEdit {
+        coords: Vector3::new(x, 0, 0),
+        old: (old, Dir::Up),
+        new: (new, Dir::Up),
+    };
+    let mut history = EditHistory::default();
+    history.record(vec![edit(0, Block::Empty, Block::Stone)]);
+    history.record(vec![
+        edit(1, Block::Dirt, Block::Empty),
+        edit(1, Block::Empty, Block::Log),
+    ]);
+    let undone = history.undo().unwrap();
+    assert_eq!(undone.last().unwrap().1, Block::Dirt);
+    assert_eq!(history.redo().unwrap().last().unwrap().1, Block::Log);
+    history.undo();
+    history.undo();
+    assert!(history.undo().is_none());
+    assert_eq!(
+        history.redo().unwrap(),
+        vec![(Vector3::new(0, 0, 0), Block::Stone, Dir::Up)]
+    );
+    // a new operation drops what could be redone
+    history.record(vec![edit(2, Block::Empty, Block::Sand)]);
+    assert!(history.redo().is_none());
+    assert_eq!(history.edits, 2);
+
+    // only the newest edits are kept
+    let many = (0..MAX_HISTORY_EDITS as i32 - 1)
+        .map(|x| edit(x, Block::Empty, Block::Stone))
+        .collect();
+    history.record(many);
+    assert_eq!(history.undo.len(), 2);
+    assert_eq!(history.edits, MAX_HISTORY_EDITS);
+}
@@ -0,0 +1,293 @@
+use specs::{Component, DenseVecStorage};
+
+use crate::blocks::Block;
+
+/// how many slots the inventory of a player has
+pub const INVENTORY_SIZE: usize = 36;
+/// the blocks a new player has a stack of in the hotbar
+const STARTING_BLOCKS: [Block; 9] = [
+    Block::Stone,
+    Block::Dirt,
+    Block::Grass,
+    Block::Sand,
+    Block::Water,
+    Block::Log,
+    Block::StoneSlab,
+    Block::StoneStairs,
+    Block::TallGrass,
+];
+/// edge length of the cube a dropped item is drawn as
+pub const DROPPED_ITEM_SIZE: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Item {
+    Block(Block),
+    StonePickaxe,
+    StoneShovel,
+}
+
+impl Item {
+    /// the item a player gets for breaking `block`
+    pub fn dropped_by(block: Block) -> Option<Item> {
+        match block {
+            Block::Empty | Block::Water | Block::TallGrass => None,
+            Block::Grass => Some(Item::Block(Block::Dirt)),
+            block => Some(Item::Block(block)),
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Item::Block(block) => block.name(),
+            Item::StonePickaxe => "stone_pickaxe",
+            Item::StoneShovel => "stone_shovel",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Item> {
+        match name {
+            "stone_pickaxe" => Some(Item::StonePickaxe),
+            "stone_shovel" => Some(Item::StoneShovel),
+            name => Block::from_name(name).map(Item::Block),
+        }
+    }
+    /// how many times faster than by hand this item breaks `block`
+    pub fn break_speed(self, block: Block) -> f32 {
+        match (self, block) {
+            (Item::StonePickaxe, Block::Stone)
+            | (Item::StonePickaxe, Block::StoneSlab)
+            | (Item::StonePickaxe, Block::StoneStairs)
+            | (Item::StonePickaxe, Block::Furnace)
+            | (Item::StonePickaxe, Block::CoalOre)
+            | (Item::StonePickaxe, Block::IronOre)
+            | (Item::StonePickaxe, Block::GoldOre) => 4.0,
+            (Item::StoneShovel, Block::Dirt)
+            | (Item::StoneShovel, Block::Grass)
+            | (Item::StoneShovel, Block::Sand) => 4.0,
+            _ => 1.0,
+        }
+    }
+    pub fn max_stack_size(self) -> u32 {
+        match self {
+            Item::Block(_) => 64,
+            Item::StonePickaxe | Item::StoneShovel => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: Item,
+    pub count: u32,
+}
+
+impl ItemStack {
+    pub fn new(item: Item, count: u32) -> Self {
+        Self { item, count }
+    }
+}
+
+/// a dropped item stack lying in the world until a player picks it up
+#[derive(Component, Debug)]
+pub struct DroppedItem(pub ItemStack);
+
+/// the items of a player, the first `HOTBAR_SIZE` slots are the ones the hotbar selects from
+#[derive(Component, Debug, Clone)]
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: vec![None; size],
+        }
+    }
+    /// the inventory of a new player
+    pub fn starting() -> Self {
+        let mut inventory = Self::new(INVENTORY_SIZE);
+        for (slot, block) in inventory.slots.iter_mut().zip(STARTING_BLOCKS.iter()) {
+            let item = Item::Block(*block);
+            *slot = Some(ItemStack::new(item, item.max_stack_size()));
+        }
+        inventory
+    }
+    /// adds as much of `stack` as fits, filling existing stacks of the same item first,
+    /// returns what didn't fit
+    pub fn add(&mut self, mut stack: ItemStack) -> Option<ItemStack> {
+        let max = stack.item.max_stack_size();
+        for slot in self.slots.iter_mut().flatten() {
+            if slot.item == stack.item && slot.count < max {
+                let moved = (max - slot.count).min(stack.count);
+                slot.count += moved;
+                stack.count -= moved;
+                if stack.count == 0 {
+                    return None;
+                }
+            }
+        }
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            let moved = max.min(stack.count);
+            *slot = Some(ItemStack::new(stack.item, moved));
+            stack.count -= moved;
+            if stack.count == 0 {
+                return None;
+            }
+        }
+        Some(stack)
+    }
+    /// the stack in the slot the hotbar has selected
+    pub fn held(&self, selected: usize) -> Option<ItemStack> {
+        self.slots.get(selected).copied().flatten()
+    }
+    /// the block in the slot the hotbar has selected, if it holds one
+    pub fn held_block(&self, selected: usize) -> Option<Block> {
+        match self.held(selected)?.item {
+            Item::Block(block) => Some(block),
+            _ => None,
+        }
+    }
+    /// takes one item out of the slot the hotbar has selected
+    pub fn take_held(&mut self, selected: usize) -> Option<Item> {
+        let slot = self.slots.get_mut(selected)?;
+        let stack = slot.as_mut()?;
+        let item = stack.item;
+        stack.count -= 1;
+        if stack.count == 0 {
+            *slot = None;
+        }
+        Some(item)
+    }
+    /// swaps the first stack of `item` into the slot the hotbar has selected,
+    /// returns false if there is none
+    pub fn select_item(&mut self, selected: usize, item: Item) -> bool {
+        if selected >= self.slots.len() {
+            return false;
+        }
+        if self.held(selected).map(|stack| stack.item) == Some(item) {
+            return true;
+        }
+        match self
+            .slots
+            .iter()
+            .position(|slot| slot.map(|stack| stack.item) == Some(item))
+        {
+            Some(slot) => {
+                self.slots.swap(slot, selected);
+                true
+            }
+            None => false,
+        }
+    }
+    /// the break speed of the held item for `block`, without a tool in hand it is 1
+    pub fn break_speed(&self, selected: usize, block: Block) -> f32 {
+        self.held(selected)
+            .map_or(1.0, |stack| stack.item.break_speed(block))
+    }
+    #[cfg(test)]
+    pub fn count(&self, item: Item) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+    /// removes `count` of `item`, taking from the last stacks first,
+    /// returns false and leaves the inventory unchanged if there are not enough
+    #[cfg(test)]
+    pub fn remove(&mut self, item: Item, mut count: u32) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+        for slot in self.slots.iter_mut().rev() {
+            if count == 0 {
+                break;
+            }
+            if let Some(stack) = slot {
+                if stack.item == item {
+                    let removed = stack.count.min(count);
+                    stack.count -= removed;
+                    count -= removed;
+                    if stack.count == 0 {
+                        *slot = None;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+#[test]
+fn item_names_round_trip() {
+    for item in Block::ALL
+        .iter()
+        .map(|block| Item::Block(*block))
+        .chain(vec![Item::StonePickaxe, Item::StoneShovel])
+    {
+        assert_eq!(Item::from_name(item.name()), Some(item));
+    }
+    assert_eq!(Item::from_name("diamond"), None);
+}
+#[test]
+fn add_fills_existing_stacks_first() {
+    let stone = Item::Block(Block::Stone);
+    let mut inventory = Inventory::new(3);
+    inventory.slots[1] = Some(ItemStack::new(stone, 60));
+    assert_eq!(inventory.add(ItemStack::new(stone, 10)), None);
+    assert_eq!(inventory.slots[1], Some(ItemStack::new(stone, 64)));
+    assert_eq!(inventory.slots[0], Some(ItemStack::new(stone, 6)));
+    assert_eq!(inventory.count(stone), 70);
+}
+#[test]
+fn add_returns_what_does_not_fit() {
+    let dirt = Item::Block(Block::Dirt);
+    let mut inventory = Inventory::new(2);
+    assert_eq!(
+        inventory.add(ItemStack::new(dirt, 150)),
+        Some(ItemStack::new(dirt, 22))
+    );
+    assert_eq!(inventory.count(dirt), 128);
+}
+#[test]
+fn remove_needs_enough_items() {
+    let sand = Item::Block(Block::Sand);
+    let mut inventory = Inventory::new(2);
+    inventory.add(ItemStack::new(sand, 70));
+    assert!(!inventory.remove(sand, 71));
+    assert_eq!(inventory.count(sand), 70);
+    assert!(inventory.remove(sand, 70));
+    assert_eq!(inventory.slots, vec![None, None]);
+}
+#[test]
+fn only_the_held_tool_is_used() {
+    let mut inventory = Inventory::new(3);
+    assert_eq!(inventory.break_speed(0, Block::Stone), 1.0);
+    inventory.add(ItemStack::new(Item::StoneShovel, 1));
+    assert_eq!(inventory.break_speed(0, Block::Stone), 1.0);
+    assert!(inventory.break_speed(0, Block::Sand) > 1.0);
+    inventory.add(ItemStack::new(Item::StonePickaxe, 1));
+    // the pickaxe is in the second slot, so it only helps once it is selected
+    assert_eq!(inventory.break_speed(0, Block::Stone), 1.0);
+    assert!(inventory.break_speed(1, Block::Stone) > 1.0);
+    assert_eq!(inventory.break_speed(1, Block::Sand), 1.0);
+    assert_eq!(inventory.break_speed(5, Block::Stone), 1.0);
+}
+#[test]
+fn the_selected_slot_is_held() {
+    let (dirt, sand) = (Item::Block(Block::Dirt), Item::Block(Block::Sand));
+    let mut inventory = Inventory::new(4);
+    inventory.slots[0] = Some(ItemStack::new(Item::StoneShovel, 1));
+    inventory.slots[3] = Some(ItemStack::new(dirt, 2));
+    assert_eq!(inventory.held_block(0), None);
+    assert!(inventory.select_item(1, dirt));
+    assert_eq!(inventory.held_block(1), Some(Block::Dirt));
+    assert_eq!(inventory.slots[3], None);
+    assert!(!inventory.select_item(1, sand));
+    assert_eq!(inventory.take_held(1), Some(dirt));
+    assert_eq!(inventory.take_held(1), Some(dirt));
+    assert_eq!(inventory.take_held(1), None);
+    assert_eq!(inventory.slots[1], None);
+    assert_eq!(inventory.take_held(9), None);
+    // a new player can place blocks right away
+    assert_eq!(Inventory::starting().held_block(0), Some(Block::Stone));
+}
@@ -0,0 +1,323 @@
+use crate::components::{FirstPersonController, Player, Position};
+use crate::erosion::HeightCache;
+use crate::items::{Inventory, Item, ItemStack};
+use crate::resources::{Time, WorldSeed};
+use crate::storage::{backups, write_atomically, WorldStorage};
+use crate::world_gen::GeneratorSettings;
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+use specs::{Join, ReadExpect, ReadStorage, World, WorldExt};
+use std::path::Path;
+
+/// the directory everything about the world is saved in
+pub const WORLD_DIRECTORY: &str = "world";
+/// the directory the backups of the world directory are kept in
+pub const BACKUP_DIRECTORY: &str = "backups";
+pub const LEVEL_FILE: &str = "level.json";
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for LevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "{}", error),
+            LevelError::Json(error) => write!(f, "invalid level: {}", error),
+        }
+    }
+}
+
+impl From<std::io::Error> for LevelError {
+    fn from(error: std::io::Error) -> Self {
+        LevelError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for LevelError {
+    fn from(error: serde_json::Error) -> Self {
+        LevelError::Json(error)
+    }
+}
+
+/// an item stack by the name of its item, so the file doesn't change when items are added
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SavedStack {
+    item: String,
+    count: u32,
+}
+
+/// what is saved of a player
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SavedPlayer {
+    position: [f32; 3],
+    yaw: f32,
+    pitch: f32,
+    inventory: Vec<Option<SavedStack>>,
+}
+
+/// what a level is captured from, so a system can save it too
+pub type LevelData<'a> = (
+    ReadExpect<'a, WorldSeed>,
+    ReadExpect<'a, GeneratorSettings>,
+    ReadExpect<'a, Time>,
+    ReadStorage<'a, Player>,
+    ReadStorage<'a, Position>,
+    ReadStorage<'a, FirstPersonController>,
+    ReadStorage<'a, Inventory>,
+);
+
+/// everything about a world that isn't in its chunks, saved as `level.json` in the world directory
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Level {
+    seed: u64,
+    #[serde(default)]
+    generator: GeneratorSettings,
+    day_time: f32,
+    players: Vec<SavedPlayer>,
+}
+
+impl Level {
+    /// takes the level out of the resources and the players of `world`
+    pub fn capture(world: &World) -> Self {
+        Self::of(&world.system_data())
+    }
+    pub fn of(
+        (seed, generator, time, players, positions, controllers, inventories): &LevelData<'_>,
+    ) -> Self {
+        let players = (players, positions, controllers, inventories)
+            .join()
+            .map(|(_, position, controller, inventory)| SavedPlayer {
+                position: position.0.into(),
+                yaw: controller.yaw,
+                pitch: controller.pitch,
+                inventory: inventory
+                    .slots
+                    .iter()
+                    .map(|slot| {
+                        slot.map(|stack| SavedStack {
+                            item: stack.item.name().to_string(),
+                            count: stack.count,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self {
+            seed: seed.0,
+            generator: **generator,
+            day_time: time.day_time,
+            players,
+        }
+    }
+    /// puts the level into `world`, the players get their saved state in the order they were saved in,
+    /// it has to happen before any chunk is generated, or the chunks are from another seed,
+    /// stacks of items that don't exist anymore are left out
+    pub fn apply(&self, world: &mut World) {
+        let mut inventories = Vec::new();
+        for player in self.players.iter() {
+            let mut inventory = Inventory::new(player.inventory.len());
+            for (slot, saved) in inventory.slots.iter_mut().zip(player.inventory.iter()) {
+                if let Some(saved) = saved {
+                    match Item::from_name(&saved.item) {
+                        Some(item) => *slot = Some(ItemStack::new(item, saved.count)),
+                        None => eprintln!(
+                            "{}: unknown item \"{}\", {} of it are left out",
+                            LEVEL_FILE, saved.item, saved.count
+                        ),
+                    }
+                }
+            }
+            inventories.push(inventory);
+        }
+        world.insert(WorldSeed(self.seed));
+        world.insert(self.generator);
+        // heights that were looked up before are from the old seed
+        world.insert(HeightCache::default());
+        world.write_resource::<Time>().set_day_time(self.day_time);
+        let players = world.read_storage::<Player>();
+        let mut positions = world.write_storage::<Position>();
+        let mut controllers = world.write_storage::<FirstPersonController>();
+        let mut player_inventories = world.write_storage::<Inventory>();
+        let joined = (
+            &players,
+            &mut positions,
+            &mut controllers,
+            &mut player_inventories,
+        )
+            .join();
+        for ((_, position, controller, inventory), (saved, saved_inventory)) in
+            joined.zip(self.players.iter().zip(inventories))
+        {
+            position.0 = Vector3::from(saved.position);
+            controller.yaw = saved.yaw;
+            controller.pitch = saved.pitch;
+            *inventory = saved_inventory;
+        }
+    }
+    pub fn load(directory: &Path) -> Result<Self, LevelError> {
+        let json = std::fs::read_to_string(directory.join(LEVEL_FILE))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+    /// saves the level into the directory of `storage` once the chunks saved before are written
+    pub fn save_after_chunks(&self, storage: &WorldStorage) -> Result<(), LevelError> {
+        let json = serde_json::to_string_pretty(self)?;
+        storage.write_file(storage.directory().join(LEVEL_FILE), json.into_bytes())?;
+        Ok(())
+    }
+    pub fn save(&self, directory: &Path) -> Result<(), LevelError> {
+        let json = serde_json::to_string_pretty(self)?;
+        write_atomically(&directory.join(LEVEL_FILE), json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// loads the level of the world directory into `world`, a world without a level stays as it is,
+/// a level that can't be read is kept aside and the one of the newest backup that can be read is used,
+/// the error is returned if there is none, as the saved chunks are from a seed that isn't known anymore
+pub fn load_level(world: &mut World) -> Result<(), LevelError> {
+    load_level_from(
+        world,
+        Path::new(WORLD_DIRECTORY),
+        Path::new(BACKUP_DIRECTORY),
+    )
+}
+
+fn load_level_from(
+    world: &mut World,
+    directory: &Path,
+    backup_directory: &Path,
+) -> Result<(), LevelError> {
+    let error = match Level::load(directory) {
+        Ok(level) => {
+            level.apply(world);
+            return Ok(());
+        }
+        Err(LevelError::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => error,
+    };
+    let path = directory.join(LEVEL_FILE);
+    let aside = path.with_extension("json.corrupt");
+    eprintln!(
+        "{} can't be loaded: {}, it is kept as {}",
+        path.display(),
+        error,
+        aside.display()
+    );
+    std::fs::copy(&path, &aside)?;
+    for backup in backups(backup_directory).unwrap_or_default() {
+        if let Ok(level) = Level::load(&backup) {
+            eprintln!("using the level of the backup {}", backup.display());
+            level.save(directory)?;
+            level.apply(world);
+            return Ok(());
+        }
+    }
+    Err(error)
+}
+
+pub fn save_level(world: &World) {
+    if let Err(error) = Level::capture(world).save(Path::new(WORLD_DIRECTORY)) {
+        eprintln!("could not save {}: {}", LEVEL_FILE, error);
+    }
+}
+
+#[test]
+fn level_round_trips_through_a_file_and_the_world() {
+    use specs::Builder;
+    fn world_with_player() -> World {
+        let mut world = World::new();
+        world.register::<Player>();
+        world.register::<Position>();
+        world.register::<FirstPersonController>();
+        world.register::<Inventory>();
+        world.insert(WorldSeed(1));
+        world.insert(GeneratorSettings::default());
+        world.insert(Time::default());
+        world.insert(HeightCache::default());
+        world
+            .create_entity()
+            .with(Player)
+            .with(Position(Vector3::new(0.5, 20.0, 0.5)))
+            .with(FirstPersonController {
+                yaw: 0.0,
+                pitch: 0.0,
+                speed: 2.5,
+                key_turn_speed: 2.5,
+                boost: 3.0,
+            })
+            .with(Inventory::new(4))
+            .build();
+        world
+    }
+    let mut world = world_with_player();
+    world.insert(WorldSeed(0xFFFF_FFFF_FFFF_FFF0));
+    world.insert(GeneratorSettings {
+        sea_level: 20,
+        ..Default::default()
+    });
+    world.write_resource::<Time>().set_day_time(3.25);
+    for (position, controller, inventory) in (
+        &mut world.write_storage::<Position>(),
+        &mut world.write_storage::<FirstPersonController>(),
+        &mut world.write_storage::<Inventory>(),
+    )
+        .join()
+    {
+        position.0 = Vector3::new(-12.5, 31.0, 700.25);
+        controller.yaw = 1.5;
+        controller.pitch = -0.25;
+        inventory.add(ItemStack::new(Item::StonePickaxe, 1));
+        inventory.add(ItemStack::new(Item::Block(crate::blocks::Block::Log), 70));
+    }
+    let level = Level::capture(&world);
+    let directory = std::env::temp_dir().join(format!("level_test_{}", std::process::id()));
+    level.save(&directory).unwrap();
+    let loaded = Level::load(&directory).unwrap();
+    assert_eq!(loaded, level);
+
+    // a level that can't be read is kept, without a backup it leaves the world alone
+    let backup_directory = directory.join("backups");
+    std::fs::write(directory.join(LEVEL_FILE), "{\"seed\": ").unwrap();
+    let mut untouched = world_with_player();
+    assert!(load_level_from(&mut untouched, &directory, &backup_directory).is_err());
+    assert_eq!(untouched.read_resource::<WorldSeed>().0, 1);
+    let aside = directory.join(LEVEL_FILE).with_extension("json.corrupt");
+    assert_eq!(std::fs::read_to_string(aside).unwrap(), "{\"seed\": ");
+    // with backups the newest one that can be read is used
+    level.save(&backup_directory.join("0")).unwrap();
+    std::fs::create_dir_all(backup_directory.join("1")).unwrap();
+    std::fs::write(backup_directory.join("1").join(LEVEL_FILE), "{").unwrap();
+    let mut from_backup = world_with_player();
+    load_level_from(&mut from_backup, &directory, &backup_directory).unwrap();
+    assert_eq!(Level::capture(&from_backup), level);
+    assert_eq!(Level::load(&directory).unwrap(), level);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    let mut restored = world_with_player();
+    loaded.apply(&mut restored);
+    assert_eq!(
+        restored.read_resource::<WorldSeed>().0,
+        0xFFFF_FFFF_FFFF_FFF0
+    );
+    assert_eq!(restored.read_resource::<GeneratorSettings>().sea_level, 20);
+    assert_eq!(restored.read_resource::<Time>().day_time, 3.25);
+    assert_eq!(Level::capture(&restored), level);
+    let inventories = restored.read_storage::<Inventory>();
+    let inventory = (&inventories).join().next().unwrap();
+    assert_eq!(inventory.count(Item::Block(crate::blocks::Block::Log)), 70);
+
+    let unknown = serde_json::to_string(&level)
+        .unwrap()
+        .replace("stone_pickaxe", "laser");
+    let unknown: Level = serde_json::from_str(&unknown).unwrap();
+    // only the stack of the removed item is lost
+    let mut without = world_with_player();
+    unknown.apply(&mut without);
+    let inventories = without.read_storage::<Inventory>();
+    let inventory = (&inventories).join().next().unwrap();
+    assert_eq!(inventory.count(Item::StonePickaxe), 0);
+    assert_eq!(inventory.count(Item::Block(crate::blocks::Block::Log)), 70);
+}
@@ -2,21 +2,41 @@
 #![warn(rust_2018_idioms)]
 #![forbid(unsafe_code)]
 
+mod behaviours;
+mod biomes;
+mod block_model;
+mod block_tick;
 mod blocks;
 mod chunk;
+mod chunk_loading;
 mod chunk_map;
 mod chunk_middle_ware;
+mod climate;
 mod components;
+// nothing crafts yet, so the recipes are only built for their tests
+#[cfg(test)]
+mod crafting;
 mod dir;
+mod erosion;
 mod flat_middleware;
+mod history;
+mod items;
+mod level;
 mod manager;
 mod math_utils;
 mod neighbours;
+mod noise;
 mod ray_intersection;
 mod resources;
+mod schematic;
+mod selection;
 mod setup;
 mod state;
+mod storage;
+mod structures;
 mod systems;
+mod world_editor;
+mod world_gen;
 
 use crate::state::State;
 
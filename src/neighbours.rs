@@ -23,6 +23,17 @@ impl<'a, T> Neighbours<T> {
         self.up = None;
         self.down = None;
     }
+    pub fn set(&mut self, dir: Dir, value: T) {
+        let neighbour = match dir {
+            Dir::North => &mut self.north,
+            Dir::South => &mut self.south,
+            Dir::West => &mut self.west,
+            Dir::East => &mut self.east,
+            Dir::Up => &mut self.up,
+            Dir::Down => &mut self.down,
+        };
+        *neighbour = Some(value);
+    }
     pub fn new() -> Self {
         Self {
             north: None,
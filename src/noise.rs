@@ -0,0 +1,107 @@
+/// mixes a seed and a point into a pseudo random number,
+/// the same seed and point always give the same number
+pub fn hash(seed: u64, x: i32, y: i32, z: i32) -> u64 {
+    let mut h = seed
+        ^ (x as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u32 as u64).wrapping_mul(0xD6E8_FEB8_6659_FD93)
+        ^ (z as u32 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    // the finalizer of splitmix64, so neighbouring points are not alike
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^ (h >> 31)
+}
+
+/// a pseudo random value from 0 to 1 for a point of the lattice
+pub fn random_2d(seed: u64, x: i32, z: i32) -> f32 {
+    random_3d(seed, x, 0, z)
+}
+
+pub fn random_3d(seed: u64, x: i32, y: i32, z: i32) -> f32 {
+    (hash(seed, x, y, z) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// value noise from 0 to 1 that changes smoothly between whole coordinates
+pub fn value_2d(seed: u64, x: f32, z: f32) -> f32 {
+    let x0 = x.floor();
+    let z0 = z.floor();
+    let tx = smooth(x - x0);
+    let tz = smooth(z - z0);
+    let (x0, z0) = (x0 as i32, z0 as i32);
+    let a = random_2d(seed, x0, z0);
+    let b = random_2d(seed, x0 + 1, z0);
+    let c = random_2d(seed, x0, z0 + 1);
+    let d = random_2d(seed, x0 + 1, z0 + 1);
+    lerp(lerp(a, b, tx), lerp(c, d, tx), tz)
+}
+
+/// `octaves` layers of value noise, every one with twice the detail and half the weight,
+/// `scale` is the size of the biggest features in blocks
+pub fn fractal_2d(seed: u64, x: f32, z: f32, scale: f32, octaves: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut weight = 1.0;
+    let mut total = 0.0;
+    let mut frequency = 1.0 / scale;
+    for octave in 0..octaves {
+        let seed = seed.wrapping_add(octave as u64);
+        sum += value_2d(seed, x * frequency, z * frequency) * weight;
+        total += weight;
+        weight *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+/// value noise from 0 to 1 that changes smoothly between whole coordinates, in three dimensions
+pub fn value_3d(seed: u64, x: f32, y: f32, z: f32) -> f32 {
+    let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+    let tx = smooth(x - x0);
+    let ty = smooth(y - y0);
+    let tz = smooth(z - z0);
+    let (x0, y0, z0) = (x0 as i32, y0 as i32, z0 as i32);
+    let plane = |y| {
+        let a = random_3d(seed, x0, y, z0);
+        let b = random_3d(seed, x0 + 1, y, z0);
+        let c = random_3d(seed, x0, y, z0 + 1);
+        let d = random_3d(seed, x0 + 1, y, z0 + 1);
+        lerp(lerp(a, b, tx), lerp(c, d, tx), tz)
+    };
+    lerp(plane(y0), plane(y0 + 1), ty)
+}
+
+/// like `fractal_2d`, `scale` is the size of the biggest features in blocks
+pub fn fractal_3d(seed: u64, position: [f32; 3], scale: f32, octaves: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut weight = 1.0;
+    let mut total = 0.0;
+    let mut frequency = 1.0 / scale;
+    for octave in 0..octaves {
+        let seed = seed.wrapping_add(octave as u64);
+        let [x, y, z] = position;
+        sum += value_3d(seed, x * frequency, y * frequency, z * frequency) * weight;
+        total += weight;
+        weight *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+#[test]
+fn noise_is_deterministic_and_smooth() {
+    for i in 0..100 {
+        let (x, z) = (i as f32 * 3.7 - 150.0, i as f32 * -1.3 + 20.0);
+        let value = fractal_2d(42, x, z, 64.0, 4);
+        assert!((0.0..=1.0).contains(&value));
+        assert_eq!(value, fractal_2d(42, x, z, 64.0, 4));
+        // one block further the value barely changes
+        assert!((value - fractal_2d(42, x + 1.0, z, 64.0, 4)).abs() < 0.2);
+    }
+    assert_ne!(value_2d(1, 0.5, 0.5), value_2d(2, 0.5, 0.5));
+}
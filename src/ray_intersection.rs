@@ -1,7 +1,14 @@
 use cgmath::{InnerSpace, Vector3};
 use specs::ReadStorage;
 
-use crate::{blocks::Block, chunk::Chunk, chunk_map::ChunkMap, components::LookedAt, dir::Dir};
+use crate::{
+    block_model::{collision_boxes, cube_face},
+    blocks::Block,
+    chunk::Chunk,
+    chunk_map::ChunkMap,
+    components::LookedAt,
+    dir::Dir,
+};
 
 pub fn ray_chunks_intersection(
     chunk_map: &ChunkMap,
@@ -30,8 +37,14 @@ pub fn ray_chunks_intersection(
                 if let Some(chunk) = chunk_map.get_chunk(chunk_coords) {
                     if let Some(chunk) = chunks.get(chunk) {
                         let block = chunk.get_block(block_coords);
-                        if block != Block::Empty {
-                            for (triangle, current_dir) in create_block_triangles().iter() {
+                        if block == Block::Empty {
+                            continue;
+                        }
+                        let facing = chunk.get_facing(block_coords);
+                        for (min, max) in
+                            collision_boxes(block.shape(), block.orientation(), facing)
+                        {
+                            for (triangle, current_dir) in box_triangles(min, max).iter() {
                                 let triangle_pos: Vector3<f32> = Vector3::new(
                                     current_coords.x as f32,
                                     current_coords.y as f32,
@@ -74,105 +87,31 @@ pub fn ray_chunks_intersection(
     nearest
 }
 
-const fn create_block_triangles() -> [(Triangle, Dir); 12] {
-    [
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 0.0, 1.0),
-                p1: Vector3::new(1.0, 0.0, 1.0),
-                p2: Vector3::new(1.0, 1.0, 1.0),
-            },
-            Dir::North,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 0.0, 1.0),
-                p1: Vector3::new(1.0, 1.0, 1.0),
-                p2: Vector3::new(0.0, 1.0, 1.0),
-            },
-            Dir::North,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 0.0, 0.0),
-                p1: Vector3::new(1.0, 1.0, 0.0),
-                p2: Vector3::new(1.0, 0.0, 0.0),
-            },
-            Dir::South,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 0.0, 0.0),
-                p1: Vector3::new(0.0, 1.0, 0.0),
-                p2: Vector3::new(1.0, 1.0, 0.0),
-            },
-            Dir::South,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(1.0, 0.0, 0.0),
-                p1: Vector3::new(1.0, 1.0, 1.0),
-                p2: Vector3::new(1.0, 0.0, 1.0),
-            },
-            Dir::East,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(1.0, 0.0, 0.0),
-                p1: Vector3::new(1.0, 1.0, 0.0),
-                p2: Vector3::new(1.0, 1.0, 1.0),
-            },
-            Dir::East,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 0.0, 0.0),
-                p1: Vector3::new(0.0, 0.0, 1.0),
-                p2: Vector3::new(0.0, 1.0, 1.0),
-            },
-            Dir::West,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 0.0, 0.0),
-                p1: Vector3::new(0.0, 1.0, 1.0),
-                p2: Vector3::new(0.0, 1.0, 0.0),
-            },
-            Dir::West,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 1.0, 0.0),
-                p1: Vector3::new(1.0, 1.0, 1.0),
-                p2: Vector3::new(1.0, 1.0, 0.0),
-            },
-            Dir::Up,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 1.0, 0.0),
-                p1: Vector3::new(0.0, 1.0, 1.0),
-                p2: Vector3::new(1.0, 1.0, 1.0),
-            },
-            Dir::Up,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 0.0, 0.0),
-                p1: Vector3::new(1.0, 0.0, 0.0),
-                p2: Vector3::new(1.0, 0.0, 1.0),
-            },
-            Dir::Down,
-        ),
-        (
-            Triangle {
-                p0: Vector3::new(0.0, 0.0, 0.0),
-                p1: Vector3::new(1.0, 0.0, 1.0),
-                p2: Vector3::new(0.0, 0.0, 1.0),
-            },
-            Dir::Down,
-        ),
-    ]
+/// the triangles of the faces of a box in a block
+fn box_triangles(min: Vector3<f32>, max: Vector3<f32>) -> Vec<(Triangle, Dir)> {
+    let mut triangles = Vec::with_capacity(12);
+    for dir in Dir::iter() {
+        let corner = |[x, y, z]: [u8; 3]| {
+            let pick = |c: u8, min: f32, max: f32| if c == 0 { min } else { max };
+            Vector3::new(
+                pick(x, min.x, max.x),
+                pick(y, min.y, max.y),
+                pick(z, min.z, max.z),
+            )
+        };
+        let face = cube_face(dir);
+        for vertices in face.chunks(3) {
+            triangles.push((
+                Triangle {
+                    p0: corner(vertices[0].0),
+                    p1: corner(vertices[1].0),
+                    p2: corner(vertices[2].0),
+                },
+                dir,
+            ));
+        }
+    }
+    triangles
 }
 
 #[derive(Debug, Clone, Copy)]
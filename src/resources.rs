@@ -1,24 +1,34 @@
+use std::collections::HashMap;
 use std::fmt::Debug;
 use ton::Player;
 use ton::Sample;
 
-use crate::state::DAY_LENGTH;
+use crate::{blocks::Block, manager::EcsUvMesh, state::DAY_LENGTH};
 
 pub struct Time {
     pub start: std::time::Instant,
+    /// the day time at `start`
+    pub start_day_time: f32,
     pub day_time: f32,
 }
 impl Default for Time {
     fn default() -> Self {
         Self {
             start: std::time::Instant::now(),
+            start_day_time: 0.5,
             day_time: 0.5,
         }
     }
 }
 impl Time {
     pub fn update(&mut self) {
-        self.day_time = self.start.elapsed().as_secs_f32() / DAY_LENGTH + 0.5;
+        self.day_time = self.start.elapsed().as_secs_f32() / DAY_LENGTH + self.start_day_time;
+    }
+    /// lets the clock go on from `day_time`, like when a world is loaded
+    pub fn set_day_time(&mut self, day_time: f32) {
+        self.start = std::time::Instant::now();
+        self.start_day_time = day_time;
+        self.day_time = day_time;
     }
 }
 
@@ -29,6 +39,15 @@ impl Default for DeltaTime {
     }
 }
 
+/// the seed all of the world generation is derived from
+#[derive(Debug, Clone, Copy)]
+pub struct WorldSeed(pub u64);
+impl Default for WorldSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
 pub struct Sink(Option<ton::Sink>);
 #[allow(dead_code)]
 impl Sink {
@@ -96,3 +115,50 @@ impl Default for SoundPlayer {
         Self::new()
     }
 }
+
+/// a cube mesh for every block, used to render blocks that are not part of a chunk
+#[derive(Default)]
+pub struct BlockMeshes(pub HashMap<Block, EcsUvMesh>);
+
+pub const CRACK_STAGES: usize = 8;
+/// a slightly enlarged cube with a crack texture for every stage of breaking a block
+pub struct CrackMeshes(pub Vec<EcsUvMesh>);
+
+/// in survival mode broken blocks drop items and placing a block uses one up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Survival,
+    Creative,
+}
+impl GameMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            GameMode::Survival => GameMode::Creative,
+            GameMode::Creative => GameMode::Survival,
+        }
+    }
+}
+
+pub const HOTBAR_SIZE: usize = 9;
+/// which of the first `HOTBAR_SIZE` slots of the inventory the player holds,
+/// selected with the number keys and the mouse wheel
+#[derive(Default)]
+pub struct Hotbar {
+    pub selected: usize,
+}
+impl Hotbar {
+    /// moves the selection by `offset` slots, wrapping around at both ends
+    pub fn scroll(&mut self, offset: i32) {
+        let size = HOTBAR_SIZE as i32;
+        self.selected = ((self.selected as i32 + offset) % size + size) as usize % HOTBAR_SIZE;
+    }
+}
+
+#[test]
+fn hotbar_scroll_wraps_around() {
+    let mut hotbar = Hotbar::default();
+    hotbar.scroll(-1);
+    assert_eq!(hotbar.selected, HOTBAR_SIZE - 1);
+    hotbar.scroll(2);
+    assert_eq!(hotbar.selected, 1);
+}
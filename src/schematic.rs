@@ -0,0 +1,200 @@
+use crate::blocks::Block;
+use crate::dir::Dir;
+use crate::selection::{Clipboard, MAX_SELECTION_VOLUME};
+use crate::storage::write_atomically;
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// the directory schematics are saved in and loaded from
+pub const SCHEMATIC_DIRECTORY: &str = "schematics";
+/// the schematic the selection wand exports to and imports from
+pub const SELECTION_SCHEMATIC: &str = "selection.json";
+/// a crumbled stone hut generation scatters over the plains
+const RUIN: &str = include_str!("../res/ruin.json");
+
+#[derive(Debug)]
+pub enum SchematicError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// the runs don't fill the schematic or refer to blocks that aren't in the palette,
+    /// or it is bigger than a selection can be
+    Malformed,
+}
+
+impl std::fmt::Display for SchematicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchematicError::Io(error) => write!(f, "{}", error),
+            SchematicError::Json(error) => write!(f, "invalid schematic: {}", error),
+            SchematicError::Malformed => {
+                write!(f, "the blocks don't match the size or it is too big")
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for SchematicError {
+    fn from(error: std::io::Error) -> Self {
+        SchematicError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SchematicError {
+    fn from(error: serde_json::Error) -> Self {
+        SchematicError::Json(error)
+    }
+}
+
+/// a box of blocks as it is saved, the blocks are named so the file doesn't change when blocks are added
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Schematic {
+    size: [i32; 3],
+    /// the names of the blocks in the schematic, the runs refer to them by their index
+    palette: Vec<String>,
+    /// runs of equal blocks by x, then y, then z, as the length, the index in the palette and the facing
+    runs: Vec<(u32, u16, u8)>,
+}
+
+impl Schematic {
+    pub fn of(clipboard: &Clipboard) -> Self {
+        let mut palette: Vec<Block> = Vec::new();
+        let mut runs: Vec<(u32, u16, u8)> = Vec::new();
+        for &(block, facing) in clipboard.blocks.iter() {
+            let index = match palette.iter().position(|b| *b == block) {
+                Some(index) => index,
+                None => {
+                    palette.push(block);
+                    palette.len() - 1
+                }
+            } as u16;
+            match runs.last_mut() {
+                Some((length, i, f)) if *i == index && *f == facing as u8 => *length += 1,
+                _ => runs.push((1, index, facing as u8)),
+            }
+        }
+        Self {
+            size: clipboard.size.into(),
+            palette: palette.iter().map(|block| block.name().into()).collect(),
+            runs,
+        }
+    }
+    /// the blocks of the schematic, blocks whose names aren't known are left empty and their names returned
+    pub fn to_clipboard(&self) -> Result<(Clipboard, Vec<String>), SchematicError> {
+        let size = Vector3::from(self.size);
+        if size.x < 0 || size.y < 0 || size.z < 0 {
+            return Err(SchematicError::Malformed);
+        }
+        let mut unknown = Vec::new();
+        let palette: Vec<Block> = self
+            .palette
+            .iter()
+            .map(|name| {
+                Block::from_name(name).unwrap_or_else(|| {
+                    unknown.push(name.clone());
+                    Block::Empty
+                })
+            })
+            .collect();
+        let volume = (size.x as u64)
+            .checked_mul(size.y as u64)
+            .and_then(|volume| volume.checked_mul(size.z as u64))
+            .filter(|&volume| volume <= MAX_SELECTION_VOLUME)
+            .ok_or(SchematicError::Malformed)? as usize;
+        let mut blocks = Vec::with_capacity(volume);
+        for &(length, index, facing) in self.runs.iter() {
+            let block = *palette
+                .get(index as usize)
+                .ok_or(SchematicError::Malformed)?;
+            let facing = Dir::iter()
+                .nth(facing as usize)
+                .ok_or(SchematicError::Malformed)?;
+            if blocks.len() + length as usize > volume {
+                return Err(SchematicError::Malformed);
+            }
+            blocks.resize(blocks.len() + length as usize, (block, facing));
+        }
+        if blocks.len() != volume {
+            return Err(SchematicError::Malformed);
+        }
+        Ok((Clipboard { size, blocks }, unknown))
+    }
+    pub fn from_json(json: &str) -> Result<Self, SchematicError> {
+        Ok(serde_json::from_str(json)?)
+    }
+    pub fn load(path: &Path) -> Result<Self, SchematicError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), SchematicError> {
+        write_atomically(path, serde_json::to_string(self)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// the blocks of the ruin generation places, only read the first time they are needed
+pub fn ruin() -> &'static Clipboard {
+    static BLOCKS: OnceLock<Clipboard> = OnceLock::new();
+    BLOCKS.get_or_init(|| {
+        let schematic = Schematic::from_json(RUIN).expect("ruin.json");
+        schematic.to_clipboard().expect("ruin.json").0
+    })
+}
+
+#[test]
+fn schematics_round_trip_and_forget_unknown_blocks() {
+    let clipboard = Clipboard {
+        size: Vector3::new(2, 3, 4),
+        blocks: (0..24)
+            .map(|i| match i {
+                0..=9 => (Block::Stone, Dir::Up),
+                10 => (Block::Furnace, Dir::East),
+                11 => (Block::Furnace, Dir::West),
+                _ => (Block::Empty, Dir::Up),
+            })
+            .collect(),
+    };
+    let schematic = Schematic::of(&clipboard);
+    assert_eq!(schematic.palette, vec!["stone", "furnace", "empty"]);
+    assert_eq!(schematic.runs.len(), 4);
+    let path = std::env::temp_dir().join(format!("schematic_test_{}.json", std::process::id()));
+    schematic.save(&path).unwrap();
+    let loaded = Schematic::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.to_clipboard().unwrap(), (clipboard.clone(), vec![]));
+
+    // a block that was removed since the schematic was saved
+    let mut renamed = loaded.clone();
+    renamed.palette[1] = "marble".into();
+    let (changed, unknown) = renamed.to_clipboard().unwrap();
+    assert_eq!(unknown, vec!["marble"]);
+    assert_eq!(
+        changed.get(Vector3::new(0, 2, 2)),
+        (Block::Empty, Dir::East)
+    );
+    assert_eq!(changed.get(Vector3::new(0, 0, 0)), (Block::Stone, Dir::Up));
+
+    let mut short = loaded;
+    short.runs.pop();
+    assert!(matches!(
+        short.to_clipboard(),
+        Err(SchematicError::Malformed)
+    ));
+    assert!(Schematic::from_json("{\"size\": [1, 1, 1]}").is_err());
+    // too big to fill, even if the runs say so
+    for &size in &[[i32::MAX, i32::MAX, 3], [1000, 1000, 1000]] {
+        let huge = Schematic {
+            size,
+            palette: vec!["stone".into()],
+            runs: vec![(u32::MAX, 0, 0)],
+        };
+        assert!(matches!(
+            huge.to_clipboard(),
+            Err(SchematicError::Malformed)
+        ));
+    }
+
+    let ruin = ruin();
+    assert_eq!(ruin.size, Vector3::new(7, 4, 7));
+    assert!(ruin.blocks.contains(&(Block::Stone, Dir::Up)));
+}
@@ -0,0 +1,236 @@
+use crate::blocks::Block;
+use crate::dir::Dir;
+use crate::history::MAX_HISTORY_EDITS;
+use cgmath::Vector3;
+use finger_paint_wgpu::lines::Line;
+
+/// a block to set, in world coordinates
+pub type BlockChange = (Vector3<i32>, Block, Dir);
+
+const SELECTION_COLOR: [f32; 4] = [1.0, 0.9, 0.2, 1.0];
+/// the most blocks a selection can have, so every operation on it can be undone
+pub const MAX_SELECTION_VOLUME: u64 = MAX_HISTORY_EDITS as u64;
+
+/// blocks copied out of the world, relative to the lowest corner of the selection they were copied from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clipboard {
+    pub size: Vector3<i32>,
+    /// by x, then y, then z
+    pub blocks: Vec<(Block, Dir)>,
+}
+
+impl Clipboard {
+    fn index(&self, position: Vector3<i32>) -> usize {
+        ((position.x * self.size.y + position.y) * self.size.z + position.z) as usize
+    }
+    pub fn get(&self, position: Vector3<i32>) -> (Block, Dir) {
+        self.blocks[self.index(position)]
+    }
+    /// the clipboard turned a quarter clockwise around the y axis, seen from above
+    pub fn rotated(&self) -> Self {
+        let size = Vector3::new(self.size.z, self.size.y, self.size.x);
+        let mut rotated = Self {
+            size,
+            blocks: vec![(Block::Empty, Dir::Up); self.blocks.len()],
+        };
+        for x in 0..self.size.x {
+            for y in 0..self.size.y {
+                for z in 0..self.size.z {
+                    let (block, facing) = self.get(Vector3::new(x, y, z));
+                    let index = rotated.index(Vector3::new(self.size.z - 1 - z, y, x));
+                    rotated.blocks[index] = (block, rotate_dir(facing));
+                }
+            }
+        }
+        rotated
+    }
+}
+
+/// turns a direction a quarter clockwise around the y axis, seen from above
+fn rotate_dir(dir: Dir) -> Dir {
+    match dir {
+        Dir::North => Dir::West,
+        Dir::West => Dir::South,
+        Dir::South => Dir::East,
+        Dir::East => Dir::North,
+        Dir::Up | Dir::Down => dir,
+    }
+}
+
+/// the two corners a player marked with the selection wand and what they copied
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// whether the mouse buttons mark corners instead of breaking and placing blocks
+    pub wand: bool,
+    pub first: Option<Vector3<i32>>,
+    pub second: Option<Vector3<i32>>,
+    pub clipboard: Option<Clipboard>,
+}
+
+impl Selection {
+    /// the lowest and the highest block of the selection, both in it
+    pub fn bounds(&self) -> Option<(Vector3<i32>, Vector3<i32>)> {
+        let (a, b) = (self.first?, self.second.unwrap_or(self.first?));
+        let min = Vector3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        let max = Vector3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+        Some((min, max))
+    }
+    /// the edges of the box around the selection
+    pub fn lines(&self) -> Vec<Line> {
+        let (min, max) = match self.bounds() {
+            Some(bounds) if self.wand => bounds,
+            _ => return Vec::new(),
+        };
+        let min = min.cast::<f32>().unwrap();
+        let max = max.cast::<f32>().unwrap() + Vector3::new(1.0, 1.0, 1.0);
+        let corner = |x: bool, y: bool, z: bool| {
+            Vector3::new(
+                if x { max.x } else { min.x },
+                if y { max.y } else { min.y },
+                if z { max.z } else { min.z },
+            )
+        };
+        let mut lines = Vec::new();
+        for &a in &[false, true] {
+            for &b in &[false, true] {
+                let edges = [
+                    (corner(false, a, b), corner(true, a, b)),
+                    (corner(a, false, b), corner(a, true, b)),
+                    (corner(a, b, false), corner(a, b, true)),
+                ];
+                for &(start, end) in edges.iter() {
+                    lines.push(Line {
+                        start,
+                        end,
+                        color: SELECTION_COLOR,
+                    });
+                }
+            }
+        }
+        lines
+    }
+}
+
+/// how many blocks are in the box, `None` if there are too many to count
+pub fn volume(min: Vector3<i32>, max: Vector3<i32>) -> Option<u64> {
+    let length = |min: i32, max: i32| (i64::from(max) - i64::from(min) + 1) as u64;
+    length(min.x, max.x)
+        .checked_mul(length(min.y, max.y))?
+        .checked_mul(length(min.z, max.z))
+}
+
+fn positions(min: Vector3<i32>, max: Vector3<i32>) -> impl Iterator<Item = Vector3<i32>> {
+    (min.x..max.x + 1).flat_map(move |x| {
+        (min.y..max.y + 1).flat_map(move |y| (min.z..max.z + 1).map(move |z| Vector3::new(x, y, z)))
+    })
+}
+
+/// every block of the box becomes `block`
+pub fn fill(min: Vector3<i32>, max: Vector3<i32>, block: Block) -> Vec<BlockChange> {
+    positions(min, max)
+        .map(|coords| (coords, block, Dir::Up))
+        .collect()
+}
+
+/// every `from` in the box becomes `to`, `get_block` gives the blocks of the world
+pub fn replace(
+    min: Vector3<i32>,
+    max: Vector3<i32>,
+    from: Block,
+    to: Block,
+    get_block: impl Fn(Vector3<i32>) -> Option<Block>,
+) -> Vec<BlockChange> {
+    positions(min, max)
+        .filter(|coords| get_block(*coords) == Some(from))
+        .map(|coords| (coords, to, Dir::Up))
+        .collect()
+}
+
+/// empties the inside of the box, so only its shell is left
+pub fn hollow(min: Vector3<i32>, max: Vector3<i32>) -> Vec<BlockChange> {
+    let one = Vector3::new(1, 1, 1);
+    let (min, max) = (min + one, max - one);
+    if min.x > max.x || min.y > max.y || min.z > max.z {
+        return Vec::new();
+    }
+    fill(min, max, Block::Empty)
+}
+
+/// the blocks of the box, blocks of chunks that aren't loaded are empty
+pub fn copy(
+    min: Vector3<i32>,
+    max: Vector3<i32>,
+    get_block: impl Fn(Vector3<i32>) -> Option<(Block, Dir)>,
+) -> Clipboard {
+    Clipboard {
+        size: max - min + Vector3::new(1, 1, 1),
+        blocks: positions(min, max)
+            .map(|coords| get_block(coords).unwrap_or((Block::Empty, Dir::Up)))
+            .collect(),
+    }
+}
+
+/// puts the clipboard into the world with its lowest corner at `origin`
+pub fn paste(clipboard: &Clipboard, origin: Vector3<i32>) -> Vec<BlockChange> {
+    positions(
+        Vector3::new(0, 0, 0),
+        clipboard.size - Vector3::new(1, 1, 1),
+    )
+    .map(|position| {
+        let (block, facing) = clipboard.get(position);
+        (origin + position, block, facing)
+    })
+    .collect()
+}
+
+#[test]
+fn bulk_edits_cover_the_selection() {
+    let selection = Selection {
+        wand: true,
+        first: Some(Vector3::new(2, 5, -1)),
+        second: Some(Vector3::new(0, 7, 1)),
+        clipboard: None,
+    };
+    let (min, max) = selection.bounds().unwrap();
+    assert_eq!((min, max), (Vector3::new(0, 5, -1), Vector3::new(2, 7, 1)));
+    assert_eq!(selection.lines().len(), 12);
+    assert_eq!(volume(min, max), Some(27));
+    assert_eq!(fill(min, max, Block::Stone).len(), 27);
+    let far = Vector3::new(i32::MAX, i32::MAX, i32::MAX);
+    assert_eq!(volume(-far, far), None);
+    let hollowed = hollow(min, max);
+    assert_eq!(
+        hollowed,
+        vec![(Vector3::new(1, 6, 0), Block::Empty, Dir::Up)]
+    );
+    let world = |coords: Vector3<i32>| {
+        if coords.y == 5 {
+            Some((Block::Dirt, Dir::Up))
+        } else if coords == Vector3::new(2, 6, 1) {
+            Some((Block::Furnace, Dir::North))
+        } else {
+            Some((Block::Empty, Dir::Up))
+        }
+    };
+    let replaced = replace(min, max, Block::Dirt, Block::Sand, |c| {
+        world(c).map(|b| b.0)
+    });
+    assert_eq!(replaced.len(), 9);
+    assert!(replaced.iter().all(|change| change.0.y == 5));
+
+    let clipboard = copy(min, max, world);
+    let mut turned = clipboard.clone();
+    for _ in 0..4 {
+        turned = turned.rotated();
+    }
+    assert_eq!(turned, clipboard);
+    // the furnace at the far corner ends up at x 0 and faces west after a quarter turn
+    let rotated = clipboard.rotated();
+    assert_eq!(
+        rotated.get(Vector3::new(0, 1, 2)),
+        (Block::Furnace, Dir::West)
+    );
+    let pasted = paste(&rotated, Vector3::new(10, 0, 10));
+    assert_eq!(pasted.len(), 27);
+    assert!(pasted.contains(&(Vector3::new(10, 1, 12), Block::Furnace, Dir::West)));
+}
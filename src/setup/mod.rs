@@ -1,11 +1,25 @@
+mod setup_block_meshes;
+mod setup_crack_meshes;
 mod setup_cross_hair;
 mod setup_highlight_cube;
+mod setup_hotbar;
 mod setup_player;
 
 use crate::{
+    behaviours::{FallingBehaviour, GrassBehaviour, WaterBehaviour},
+    block_tick::{BlockBehaviours, BlockTicks},
+    blocks::Block,
     chunk::{Chunk, CHUNK_SIZE},
+    chunk_loading::ChunkTickets,
     components::LookedAt,
-    resources::SoundPlayer,
+    erosion::HeightCache,
+    history::EditHistory,
+    items::{DroppedItem, Inventory},
+    level::{BACKUP_DIRECTORY, WORLD_DIRECTORY},
+    resources::{GameMode, Hotbar, SoundPlayer, WorldSeed},
+    selection::Selection,
+    storage::{back_up, WorldStorage, KEPT_BACKUPS},
+    world_gen::GeneratorSettings,
 };
 use crate::{chunk_map::ChunkMap, components::*};
 use crate::{
@@ -22,14 +36,19 @@ use specs::Builder;
 use specs::Entity;
 use specs::{World, WorldExt};
 use std::f32::consts::PI;
+use std::path::Path;
 
+pub use setup_block_meshes::setup_block_meshes;
+pub use setup_crack_meshes::setup_crack_meshes;
 pub use setup_cross_hair::setup_cross_hair;
 pub use setup_highlight_cube::setup_highlight_cube;
+pub use setup_hotbar::setup_hotbar;
 pub use setup_player::setup_player;
 
 pub fn setup(world: &mut World) {
     world.register::<Position>();
     world.register::<Velocity>();
+    world.register::<BoundingBox>();
     world.register::<Scale>();
     world.register::<Rotation>();
     world.register::<Model>();
@@ -41,11 +60,20 @@ pub fn setup(world: &mut World) {
     world.register::<Chunk>();
     world.register::<ChunkMesh>();
     world.register::<Player>();
+    world.register::<ChunkTicket>();
     world.register::<FlatMesh>();
     world.register::<LookedAt>();
     world.register::<LookingAtMarker>();
     world.register::<BlockHighlightCube>();
+    world.register::<BreakProgress>();
+    world.register::<CrackOverlay>();
     world.register::<Sun>();
+    world.register::<FallingBlock>();
+    world.register::<FlatInstances>();
+    world.register::<HotbarIcon>();
+    world.register::<HotbarFrame>();
+    world.register::<DroppedItem>();
+    world.register::<Inventory>();
     world.insert(UvMeshManager::default());
     world.insert(ModelManager::default());
     world.insert(DeltaTime(0.0));
@@ -62,5 +90,32 @@ pub fn setup(world: &mut World) {
             aspect: 1.0,
         },
     ));
+    world.insert(WorldSeed::default());
+    world.insert(GeneratorSettings::default());
     world.insert(ChunkMap::new());
+    world.insert(HeightCache::default());
+    // a copy of the world as it was before this session, in case it gets damaged
+    let directory = Path::new(WORLD_DIRECTORY);
+    if let Err(error) = back_up(directory, Path::new(BACKUP_DIRECTORY), KEPT_BACKUPS) {
+        eprintln!("could not back up the world: {}", error);
+    }
+    world.insert(WorldStorage::open(directory));
+    let mut tickets = ChunkTickets::default();
+    // the area around the spawn stays loaded wherever the players are
+    let spawn = ChunkTicket {
+        radius: 2,
+        priority: 0,
+    };
+    tickets.set("spawn", Vector3::new(0, 0, 0), spawn);
+    world.insert(tickets);
+    world.insert(BlockTicks::new());
+    world.insert(EditHistory::default());
+    world.insert(Selection::default());
+    world.insert(Hotbar::default());
+    world.insert(GameMode::Creative);
+    let mut behaviours = BlockBehaviours::new();
+    behaviours.register(Block::Grass, GrassBehaviour);
+    behaviours.register(Block::Sand, FallingBehaviour);
+    behaviours.register(Block::Water, WaterBehaviour);
+    world.insert(behaviours);
 }
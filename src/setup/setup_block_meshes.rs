@@ -0,0 +1,58 @@
+use super::*;
+use crate::{
+    block_model::model_faces,
+    blocks::{Atlas, Block, UV_GRID},
+    dir::Dir,
+    resources::BlockMeshes,
+};
+use cgmath::Vector2;
+use finger_paint_wgpu::{
+    texture::Texture,
+    uv_mesh::{UvMeshMiddleWare, UvVertex},
+    wgpu::FilterMode,
+    WgpuRenderer,
+};
+use std::collections::HashMap;
+
+/// creates a textured model for every block, the model spans from its position to position + 1
+pub fn setup_block_meshes(world: &mut World) {
+    let texture = {
+        let renderer = world.fetch::<WgpuRenderer>();
+        let (device, queue) = renderer.device_and_queue();
+        Texture::load(
+            device,
+            queue,
+            "atlas.png",
+            FilterMode::Nearest,
+            FilterMode::Nearest,
+            false,
+        )
+        .unwrap()
+    };
+    let mut meshes = HashMap::new();
+    {
+        let atlas = world.fetch::<Atlas>();
+        let mut uv_mesh_middleware = world.fetch_mut::<UvMeshMiddleWare>();
+        let mut uv_mesh_manager = world.fetch_mut::<UvMeshManager>();
+        for block in Block::ALL.iter().filter(|b| **b != Block::Empty) {
+            let mut vertices = vec![];
+            let faces = model_faces(block.shape(), block.orientation(), Dir::Up);
+            for face in faces.iter() {
+                let uvs = atlas.uvs_of_block_index(*block as usize, face.side);
+                let normal: Vector3<i32> = face.normal.into();
+                let normal = Vector3::new(normal.x as f32, normal.y as f32, normal.z as f32);
+                for (position, [u, v]) in face.vertices.iter() {
+                    let uv = atlas.all_uvs[uvs[v * UV_GRID + u] as usize];
+                    vertices.push(UvVertex::new(
+                        position.cast::<f32>().unwrap() / 2.0,
+                        normal,
+                        Vector2::new(uv[0], uv[1]),
+                    ));
+                }
+            }
+            let mesh = uv_mesh_middleware.create_uv_mesh(vertices, None, &texture);
+            meshes.insert(*block, uv_mesh_manager.insert(mesh));
+        }
+    }
+    world.insert(BlockMeshes(meshes));
+}
@@ -0,0 +1,99 @@
+use super::*;
+use crate::{
+    block_model::cube_face,
+    components::CrackOverlay,
+    dir::Dir,
+    resources::{CrackMeshes, CRACK_STAGES},
+};
+use cgmath::Vector2;
+use finger_paint_wgpu::{
+    texture::Texture,
+    uv_mesh::{UvMeshMiddleWare, UvVertex},
+    wgpu::FilterMode,
+    WgpuRenderer,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const CRACK_TEXTURE_SIZE: u32 = 16;
+const CRACK_BRANCHES: usize = 5;
+const CRACK_BRANCH_LENGTH: usize = 12;
+
+/// creates the crack meshes and the overlay entity showing them for `player`
+pub fn setup_crack_meshes(world: &mut World, player: Entity) -> Entity {
+    let stages = crack_stages();
+    let mut vertices = vec![];
+    for dir in Dir::iter() {
+        let normal: Vector3<i32> = dir.into();
+        let normal = Vector3::new(normal.x as f32, normal.y as f32, normal.z as f32);
+        for (position, corner) in cube_face(dir).iter() {
+            let uv = Vector2::new((corner % 2) as f32, (corner / 2) as f32);
+            // slightly bigger than the block, so the cracks don't fight with its faces
+            let position = Vector3::new(position[0], position[1], position[2])
+                .cast::<f32>()
+                .unwrap()
+                * 1.01
+                - Vector3::new(0.005, 0.005, 0.005);
+            vertices.push(UvVertex::new(position, normal, uv));
+        }
+    }
+    let mut meshes = Vec::with_capacity(CRACK_STAGES);
+    for stage in 0..CRACK_STAGES {
+        let mut data = vec![0; (CRACK_TEXTURE_SIZE * CRACK_TEXTURE_SIZE * 4) as usize];
+        for (i, pixel_stage) in stages.iter().enumerate() {
+            if matches!(pixel_stage, Some(s) if *s <= stage) {
+                data[i * 4 + 3] = 200;
+            }
+        }
+        let texture = {
+            let renderer = world.fetch::<WgpuRenderer>();
+            let (device, queue) = renderer.device_and_queue();
+            Texture::from_raw(
+                device,
+                queue,
+                (CRACK_TEXTURE_SIZE, CRACK_TEXTURE_SIZE),
+                &data,
+                FilterMode::Nearest,
+                FilterMode::Nearest,
+                false,
+            )
+        };
+        let mut uv_mesh =
+            world
+                .fetch_mut::<UvMeshMiddleWare>()
+                .create_uv_mesh(vertices.clone(), None, &texture);
+        uv_mesh.transparent = true;
+        meshes.push(world.fetch_mut::<UvMeshManager>().insert(uv_mesh));
+    }
+    world.insert(CrackMeshes(meshes));
+    world
+        .create_entity()
+        .with(Position(Vector3::new(0.0, 0.0, 0.0)))
+        .with(CrackOverlay(player))
+        .build()
+}
+
+/// the stage at which every pixel of the crack texture becomes part of the crack,
+/// the cracks are random walks from the center that grow with every stage
+fn crack_stages() -> Vec<Option<usize>> {
+    let size = CRACK_TEXTURE_SIZE as i32;
+    let mut stages = vec![None; (size * size) as usize];
+    let mut rng = StdRng::seed_from_u64(0);
+    for branch in 0..CRACK_BRANCHES {
+        let angle = (branch as f32 + rng.gen_range(0.0, 0.5)) / CRACK_BRANCHES as f32
+            * std::f32::consts::PI
+            * 2.0;
+        let (mut x, mut y) = ((size / 2) as f32, (size / 2) as f32);
+        for step in 0..CRACK_BRANCH_LENGTH {
+            let angle = angle + rng.gen_range(-0.8, 0.8);
+            x += angle.cos();
+            y += angle.sin();
+            if x < 0.0 || y < 0.0 || x >= size as f32 || y >= size as f32 {
+                break;
+            }
+            let stage = step * CRACK_STAGES / CRACK_BRANCH_LENGTH;
+            let pixel = &mut stages[(y as i32 * size + x as i32) as usize];
+            *pixel = Some(pixel.map_or(stage, |s: usize| s.min(stage)));
+        }
+    }
+    stages
+}
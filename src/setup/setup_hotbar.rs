@@ -0,0 +1,82 @@
+use super::*;
+use crate::{
+    blocks::{Atlas, Block},
+    components::{HotbarFrame, HotbarIcon},
+    flat_middleware::FlatMiddleWare,
+    items::Item,
+};
+use image::RgbaImage;
+
+/// the tools have no texture, so they are drawn from these, `#` is stone and `|` is wood
+const TOOL_ICONS: [(Item, [&str; 8]); 2] = [
+    (
+        Item::StonePickaxe,
+        [
+            ".######.", "##.||.##", "#..||..#", "...||...", "...||...", "...||...", "...||...",
+            "...||...",
+        ],
+    ),
+    (
+        Item::StoneShovel,
+        [
+            "...||...", "...||...", "...||...", "...||...", "..####..", ".######.", ".######.",
+            "..####..",
+        ],
+    ),
+];
+
+/// the picture of a tool, flipped vertically for the flat meshes like the block icons
+fn tool_icon(rows: &[&str; 8]) -> RgbaImage {
+    RgbaImage::from_fn(8, 8, |x, y| {
+        let color = match rows[7 - y as usize].as_bytes()[x as usize] {
+            b'#' => [125, 125, 125, 255],
+            b'|' => [110, 80, 45, 255],
+            _ => [0, 0, 0, 0],
+        };
+        image::Rgba(color)
+    })
+}
+
+pub fn setup_hotbar(world: &mut World) {
+    let frame_color: [u8; 4] = [64, 64, 64, 255];
+    let selected_frame_color: [u8; 4] = [255, 255, 255, 255];
+    for (color, selected) in [(frame_color, false), (selected_frame_color, true)].iter() {
+        let mesh = world
+            .fetch_mut::<FlatMiddleWare>()
+            .load_flat_mesh_from_raw((1, 1), color);
+        world
+            .create_entity()
+            .with(FlatMesh(mesh))
+            .with(FlatInstances::default())
+            .with(HotbarFrame {
+                selected: *selected,
+            })
+            .build();
+    }
+    let mut icons: Vec<(Item, RgbaImage)> = Block::ALL
+        .iter()
+        .filter(|b| **b != Block::Empty)
+        .map(|block| {
+            (
+                Item::Block(*block),
+                world.fetch::<Atlas>().block_icon(*block),
+            )
+        })
+        .collect();
+    icons.extend(
+        TOOL_ICONS
+            .iter()
+            .map(|(item, rows)| (*item, tool_icon(rows))),
+    );
+    for (item, icon) in icons {
+        let mesh = world
+            .fetch_mut::<FlatMiddleWare>()
+            .load_flat_mesh_from_raw(icon.dimensions(), &icon);
+        world
+            .create_entity()
+            .with(FlatMesh(mesh))
+            .with(FlatInstances::default())
+            .with(HotbarIcon(item))
+            .build();
+    }
+}
@@ -1,14 +1,23 @@
 use super::*;
 use crate::{
     components::{self, LookingAtMarker, Model},
+    erosion::HeightCache,
     manager::EcsModelHandle,
+    world_gen::GeneratorSettings,
 };
 use cgmath::{Matrix3, SquareMatrix};
 
 pub fn setup_player(world: &mut World, sphere_model: EcsModelHandle) -> Entity {
+    let seed = world.read_resource::<WorldSeed>().0;
+    // standing on the ground, with the eyes at the top of the bounding box
+    let settings = *world.read_resource::<GeneratorSettings>();
+    let ground = world
+        .write_resource::<HeightCache>()
+        .height(seed, &settings, 0, 0);
+    let eye_height = ground as f32 + 1.5;
     let player = world
         .create_entity()
-        .with(Position(Vector3::new(0.0, 0.0, 0.0)))
+        .with(Position(Vector3::new(0.5, eye_height, 0.5)))
         .with(Rotation(Matrix3::identity()))
         .with(FirstPersonController {
             yaw: 0.0,
@@ -19,8 +28,19 @@ pub fn setup_player(world: &mut World, sphere_model: EcsModelHandle) -> Entity {
         })
         .with(components::Camera { fov: PI / 2.0 })
         .with(Player)
+        .with(ChunkTicket {
+            radius: 2,
+            priority: 1,
+        })
+        .with(Inventory::starting())
+        // the position of the player is at eye height
+        .with(BoundingBox {
+            min: Vector3::new(-0.3, -1.5, -0.3),
+            max: Vector3::new(0.3, 0.2, 0.3),
+        })
         .build();
     setup_highlight_cube(world, player);
+    setup_crack_meshes(world, player);
     world
         .create_entity()
         .with(Position(Vector3::new(0.0, 0.0, 0.0)))
@@ -1,4 +1,8 @@
-use crate::resources::DeltaTime;
+use crate::history::EditHistory;
+use crate::level::{load_level, save_level};
+use crate::resources::{DeltaTime, GameMode};
+use crate::selection::Selection;
+use crate::storage::{ChunkRecord, WorldStorage};
 use crate::systems;
 use crate::{
     blocks::Atlas,
@@ -28,6 +32,8 @@ use std::{
 };
 
 pub const DAY_LENGTH: f32 = 60.0;
+pub const GRAVITY: f32 = 20.0;
+pub const TERMINAL_VELOCITY: f32 = 30.0;
 #[allow(dead_code)]
 pub struct State {
     world: World,
@@ -92,6 +98,15 @@ impl State {
         let sphere = model_middleware.load_model_obj("./res/sphere.obj").unwrap();
         let sphere = world.fetch_mut::<ModelManager>().insert(sphere);
         let player = setup_player(&mut world, sphere);
+        if let Err(error) = load_level(&mut world) {
+            eprintln!(
+                "the level can't be loaded from the world or its backups: {}",
+                error
+            );
+            std::process::exit(1);
+        }
+        // right away, so the seed of the saved chunks is known even if the game crashes
+        save_level(&world);
         let cross_hair = setup_cross_hair(&mut world);
 
         let mut renderer = world.fetch_mut::<WgpuRenderer>();
@@ -122,7 +137,7 @@ impl State {
         let atlas = Atlas::load();
         world
             .fetch_mut::<ChunkMeshMiddleWare>()
-            .load_uvs(&atlas.all_uvs);
+            .load_uvs(&atlas.all_uvs, &atlas.all_animations);
         let vsync = true;
         renderer.enable_vsync(vsync);
         renderer.set_shadow_resolution([1024, 1024]);
@@ -130,6 +145,8 @@ impl State {
         renderer.enable_shadows(true);
         drop(renderer);
         world.insert(atlas);
+        setup_block_meshes(&mut world);
+        setup_hotbar(&mut world);
         world
             .create_entity()
             .with(Position(Vector3::new(-10.0, 20.0, 20.0)))
@@ -155,6 +172,22 @@ impl State {
     }
 }
 
+impl Drop for State {
+    fn drop(&mut self) {
+        let mut records = Vec::new();
+        for chunk in (&mut self.world.write_storage::<Chunk>()).join() {
+            if chunk.unsaved {
+                records.push(ChunkRecord::of(chunk));
+                chunk.unsaved = false;
+            }
+        }
+        let mut storage = self.world.write_resource::<WorldStorage>();
+        storage.save(records);
+        storage.flush();
+        save_level(&self.world);
+    }
+}
+
 impl simple_winit::WindowLoop for State {
     fn init(&mut self, input: Arc<Mutex<Input>>) {
         self.world.insert(input);
@@ -184,26 +217,22 @@ impl simple_winit::WindowLoop for State {
         }
 
         self.text_middleware.paragraphs()[0].sections[0].text = format!(
-            "direction: {:?}\nposition:{:?}\nfps:{}\n",
+            "direction: {:?}\nposition:{:?}\nfps:{}\nmode: {:?}\n",
             self.world.fetch::<Camera>().get_direction(),
             self.world.fetch::<Camera>().get_position(),
-            1.0 / dt
+            1.0 / dt,
+            *self.world.fetch::<GameMode>()
         );
 
         if input.key_pressed(VirtualKeyCode::V) {
             self.vsync = !self.vsync;
             renderer.enable_vsync(self.vsync);
         }
-        if input.key_pressed(VirtualKeyCode::Y) {
-            let chunks = self.world.read_storage::<Chunk>();
-            let entities = self.world.entities();
-            let mut to_be_removed = Vec::new();
-            for (_, entity) in (&chunks, &entities).join() {
-                to_be_removed.push(entity);
-            }
-            for entity in to_be_removed {
-                entities.delete(entity).unwrap();
-            }
+        if input.key_pressed(VirtualKeyCode::G) {
+            let mut game_mode = self.world.write_resource::<GameMode>();
+            *game_mode = game_mode.toggled();
+            // what was done in the other mode can't be undone, or blocks could be gained by switching
+            *self.world.write_resource::<EditHistory>() = EditHistory::default();
         }
         drop(input);
         drop(input_arc);
@@ -218,15 +247,23 @@ impl simple_winit::WindowLoop for State {
             .with(systems::TransformRealLights     , "TransformRealLights"     , &[                          ])
             .with(systems::GenerateChunks          , "GenerateChunks"          , &[                          ])
             .with(systems::BreakBlocks             , "BreakBlocks"             , &["FirstPersonController"   ])
-            .with(systems::PlaceBlocks             , "PlaceBlocks"             , &["BreakBlocks"             ])
-            .with(systems::UpdateNeighbouringChunks, "UpdateNeighbouringChunks", &["PlaceBlocks"             ])
+            .with(systems::HotbarInput             , "HotbarInput"             , &[                          ])
+            .with(systems::PlaceBlocks             , "PlaceBlocks"             , &["BreakBlocks", "HotbarInput"])
+            .with(systems::EditSelection           , "EditSelection"           , &["PlaceBlocks"             ])
+            .with(systems::UndoEdits               , "UndoEdits"               , &["EditSelection"           ])
+            .with(systems::TickBlocks              , "TickBlocks"              , &["UndoEdits"               ])
+            .with(systems::FallingBlocks           , "FallingBlocks"           , &["TickBlocks"              ])
+            .with(systems::DroppedItems            , "DroppedItems"            , &["FallingBlocks"           ])
+            .with(systems::UpdateNeighbouringChunks, "UpdateNeighbouringChunks", &["DroppedItems"            ])
             .with(systems::RemoveChunks            , "RemoveChunks"            , &["UpdateNeighbouringChunks"])
+            .with(systems::Autosave                , "Autosave"                , &["RemoveChunks"            ])
             .with(systems::LookingAtSystem         , "LookingAtSystem"         , &["RemoveChunks"            ])
             .with(systems::LookingAtMarkerSystem   , "LookingAtMarkerSystem"   , &["LookingAtSystem"         ])
             .with(systems::BlockHighlighting       , "BlockHighlighting"       , &["LookingAtSystem"         ])
             .with(systems::RenderUvMeshes          , "RenderUvMeshes"          , &["BlockHighlighting"       ])
             .with(systems::RenderModels            , "RenderModels"            , &[                          ])
-            .with(systems::RenderFlatMeshes        , "RenderFlatMeshes"        , &[                          ])
+            .with(systems::HotbarDisplay           , "HotbarDisplay"           , &["HotbarInput"             ])
+            .with(systems::RenderFlatMeshes        , "RenderFlatMeshes"        , &["HotbarDisplay"           ])
             .with(systems::ChunkMeshGeneration     , "ChunkMeshGeneration"     , &[                          ])
             .with(systems::SunSystem               , "SunSystem"               , &[                          ])
             .with(systems::UpdateCamera            , "UpdateCamera"            , &["ChunkMeshGeneration"     ])
@@ -234,6 +271,8 @@ impl simple_winit::WindowLoop for State {
             .dispatch(&self.world);
         }
         self.world.maintain();
+        self.lines.lines = self.world.fetch::<Selection>().lines();
+        self.lines.update();
     }
     fn render(&mut self, window: &Window) {
         let mut renderer = self.world.fetch_mut::<WgpuRenderer>();
@@ -248,6 +287,8 @@ impl simple_winit::WindowLoop for State {
         let uv_meshes = uv_mesh_manager.get_all();
         let chunks = self.world.read_component::<ChunkMesh>();
         let chunks = chunks.as_slice().iter().map(|m| &m.0);
+        let chunk_mesh_middleware = self.world.fetch::<ChunkMeshMiddleWare>();
+        let eye = self.world.fetch::<Camera>().get_position();
         let flat_middleware = self.world.fetch::<FlatMiddleWare>();
         let flat_meshes = self.world.read_component::<FlatMesh>();
         let flat_meshes = flat_meshes.as_slice().iter().map(|m| &m.0);
@@ -256,9 +297,10 @@ impl simple_winit::WindowLoop for State {
         renderer.render(
             window,
             &mut [
-                &mut self.world.fetch::<ChunkMeshMiddleWare>().prepare(chunks),
+                &mut chunk_mesh_middleware.prepare(chunks.clone()),
                 &mut self.model_middleware.prepare(models),
                 &mut uv_mesh_middleware.prepare(uv_meshes),
+                &mut chunk_mesh_middleware.prepare_translucent(chunks, eye),
                 &mut self.text_middleware,
                 &mut flat_middleware.prepare(flat_meshes),
                 &mut self.lines,
@@ -0,0 +1,258 @@
+use super::region::{
+    region_of, region_path, write_atomically, ChunkRecord, Region, StorageError, REGION_CHUNKS,
+};
+use crate::blocks::Block;
+use crate::chunk_map::ChunkMap;
+use crate::dir::Dir;
+use cgmath::Vector3;
+use std::collections::{hash_map::Entry, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// how many seconds pass between two autosaves
+pub const AUTOSAVE_INTERVAL: f32 = 30.0;
+
+/// a region that was read or saved
+struct CachedRegion {
+    region: Region,
+    /// how many writes of it are queued, it is only forgotten once its file is as new as it
+    writes: usize,
+}
+
+type Regions = Arc<Mutex<HashMap<(i32, i32), CachedRegion>>>;
+
+/// what the thread writes
+enum Job {
+    Region((i32, i32)),
+    File(PathBuf, Vec<u8>),
+}
+
+/// the saved chunks of a world, the region files are written on a thread of their own so saving doesn't stall the game
+pub struct WorldStorage {
+    directory: PathBuf,
+    /// the regions that were read or saved and not forgotten since, always newer than or as new as their files
+    regions: Regions,
+    /// tells the thread which regions changed
+    sender: Option<Sender<Job>>,
+    thread: Option<JoinHandle<()>>,
+    /// seconds since the last autosave
+    pub since_save: f32,
+}
+
+impl WorldStorage {
+    pub fn open(directory: &Path) -> Self {
+        let directory = directory.to_path_buf();
+        let regions: Regions = Default::default();
+        let (sender, receiver) = channel::<Job>();
+        let thread = {
+            let directory = directory.clone();
+            let regions = regions.clone();
+            std::thread::spawn(move || {
+                for job in receiver {
+                    let (path, bytes, region) = match job {
+                        // only the bytes are made while holding the lock, the slow part is writing them
+                        Job::Region(region) => (
+                            region_path(&directory, region),
+                            regions.lock().unwrap()[&region].region.to_bytes(),
+                            Some(region),
+                        ),
+                        Job::File(path, bytes) => (path, bytes, None),
+                    };
+                    if let Err(error) = write_atomically(&path, &bytes) {
+                        eprintln!("could not save {}: {}", path.display(), error);
+                    }
+                    if let Some(region) = region {
+                        if let Some(cached) = regions.lock().unwrap().get_mut(&region) {
+                            cached.writes -= 1;
+                        }
+                    }
+                }
+            })
+        };
+        Self {
+            directory,
+            regions,
+            sender: Some(sender),
+            thread: Some(thread),
+            since_save: 0.0,
+        }
+    }
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+    fn with_region<T>(
+        &self,
+        region: (i32, i32),
+        f: impl FnOnce(&mut Region) -> T,
+    ) -> Result<T, StorageError> {
+        let mut regions = self.regions.lock().unwrap();
+        let cached = match regions.entry(region) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(CachedRegion {
+                region: Region::load(&region_path(&self.directory, region))?,
+                writes: 0,
+            }),
+        };
+        Ok(f(&mut cached.region))
+    }
+    /// the saved chunk at `position`, or nothing if it has never been saved
+    pub fn load(&self, position: Vector3<i32>) -> Result<Option<ChunkRecord>, StorageError> {
+        self.with_region(region_of(position), |region| region.get(position))?
+    }
+    /// saves the chunks, they can be loaded again right away, but their files are written later
+    pub fn save(&self, records: Vec<ChunkRecord>) {
+        let mut changed = Vec::new();
+        for record in records.iter() {
+            let region = region_of(record.position);
+            match self.with_region(region, |region| region.insert(record)) {
+                Ok(()) if !changed.contains(&region) => changed.push(region),
+                Ok(()) => {}
+                Err(error) => eprintln!("could not save chunk {:?}: {}", record.position, error),
+            }
+        }
+        if let Some(sender) = &self.sender {
+            let mut regions = self.regions.lock().unwrap();
+            for region in changed {
+                regions.get_mut(&region).unwrap().writes += 1;
+                sender.send(Job::Region(region)).unwrap();
+            }
+        }
+    }
+    /// forgets the regions whose files are written and that no chunk near the players is in,
+    /// `needed` gets the chunk column in the middle of a region and how many chunk columns around it are in it
+    pub fn forget_regions(&self, needed: impl Fn(Vector3<i32>, i32) -> bool) {
+        let middle = |region: i32| region * REGION_CHUNKS + REGION_CHUNKS / 2;
+        self.regions.lock().unwrap().retain(|&(x, z), cached| {
+            cached.writes > 0 || needed(Vector3::new(middle(x), 0, middle(z)), REGION_CHUNKS)
+        });
+    }
+    /// writes the file after the chunks saved so far, so it is never newer than them when the game dies
+    pub fn write_file(&self, path: PathBuf, bytes: Vec<u8>) -> std::io::Result<()> {
+        match &self.sender {
+            Some(sender) => {
+                sender.send(Job::File(path, bytes)).unwrap();
+                Ok(())
+            }
+            None => write_atomically(&path, &bytes),
+        }
+    }
+    /// sets blocks of saved chunks in world coordinates, every chunk is loaded and saved once,
+    /// blocks of chunks that were never saved or can't be read are left out
+    pub fn change_blocks(&self, changes: Vec<(Vector3<i32>, Block, Dir)>) {
+        let mut by_chunk = HashMap::new();
+        for (coords, block, facing) in changes {
+            let (chunk, position) = ChunkMap::coords_to_chunk_coords_and_block(coords);
+            by_chunk
+                .entry(chunk)
+                .or_insert_with(Vec::new)
+                .push((position, block, facing));
+        }
+        let mut records = Vec::new();
+        for (chunk, changes) in by_chunk {
+            match self.load(chunk) {
+                Ok(Some(mut record)) => {
+                    // in order, so the last change of a block wins
+                    for (position, block, facing) in changes {
+                        let (x, y, z) = (
+                            position.x as usize,
+                            position.y as usize,
+                            position.z as usize,
+                        );
+                        record.blocks[x][y][z] = block;
+                        record.facings[x][y][z] = facing;
+                    }
+                    records.push(record);
+                }
+                Ok(None) => eprintln!("chunk {:?} to change is not saved", chunk),
+                Err(error) => eprintln!("could not change chunk {:?}: {}", chunk, error),
+            }
+        }
+        self.save(records);
+    }
+    /// waits until every saved chunk is written
+    pub fn flush(&mut self) {
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+impl Drop for WorldStorage {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[test]
+fn saved_chunks_load_again_after_the_files_are_written() {
+    use crate::blocks::Block;
+    use crate::chunk::Chunk;
+    let directory = std::env::temp_dir().join(format!("storage_test_{}", std::process::id()));
+    let mut storage = WorldStorage::open(&directory);
+    let records: Vec<ChunkRecord> = [(0, 0, 0), (7, 2, 7), (8, 0, -1)]
+        .iter()
+        .map(|&(x, y, z)| {
+            let mut chunk = Chunk::empty(Vector3::new(x, y, z));
+            chunk.set_block(Vector3::new(x as u16, 0, 0), Block::Stone);
+            ChunkRecord::of(&chunk)
+        })
+        .collect();
+    storage.save(records.clone());
+    let file = directory.join("level.json");
+    storage.write_file(file.clone(), b"{}".to_vec()).unwrap();
+    // before the thread got to it
+    assert_eq!(
+        storage.load(records[1].position).unwrap(),
+        Some(records[1].clone())
+    );
+    storage.flush();
+    assert_eq!(std::fs::read(&file).unwrap(), b"{}");
+    let reopened = WorldStorage::open(&directory);
+    for record in records.iter() {
+        assert_eq!(
+            reopened.load(record.position).unwrap(),
+            Some(record.clone())
+        );
+    }
+    assert_eq!(reopened.load(Vector3::new(1, 0, 0)).unwrap(), None);
+    // forgotten regions are read again when they are needed
+    reopened.forget_regions(|column, _| column.x < 0);
+    assert!(reopened.regions.lock().unwrap().is_empty());
+    storage.forget_regions(|column, _| column.x > 8);
+    assert_eq!(storage.regions.lock().unwrap().len(), 1);
+    assert_eq!(
+        reopened.load(records[2].position).unwrap(),
+        Some(records[2].clone())
+    );
+    drop(reopened);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn blocks_of_unloaded_chunks_are_changed_where_they_are_saved() {
+    use crate::chunk::Chunk;
+    let directory = std::env::temp_dir().join(format!("change_test_{}", std::process::id()));
+    let storage = WorldStorage::open(&directory);
+    let mut chunk = Chunk::empty(Vector3::new(-1, 0, 0));
+    chunk.set_block(Vector3::new(15, 1, 2), Block::Stone);
+    storage.save(vec![ChunkRecord::of(&chunk)]);
+    storage.change_blocks(vec![
+        (Vector3::new(-1, 1, 2), Block::Dirt, Dir::Up),
+        (Vector3::new(-1, 1, 2), Block::Furnace, Dir::East),
+        (Vector3::new(-2, 3, 4), Block::Sand, Dir::Up),
+        // never saved
+        (Vector3::new(0, 1, 2), Block::Sand, Dir::Up),
+    ]);
+    drop(storage);
+    let reopened = WorldStorage::open(&directory);
+    let record = reopened.load(Vector3::new(-1, 0, 0)).unwrap().unwrap();
+    assert_eq!(record.blocks[15][1][2], Block::Furnace);
+    assert_eq!(record.facings[15][1][2], Dir::East);
+    assert_eq!(record.blocks[14][3][4], Block::Sand);
+    assert_eq!(reopened.load(Vector3::new(0, 0, 0)).unwrap(), None);
+    drop(reopened);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,90 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// how many backups of the world are kept, older ones are deleted
+pub const KEPT_BACKUPS: usize = 3;
+
+fn copy_directory(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            copy_directory(&path, &to.join(entry.file_name()))?;
+        } else if path.extension() != Some(OsStr::new("tmp")) {
+            // half written files are left out
+            std::fs::copy(&path, to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// the numbers of the backups in `backups`, the oldest first
+fn numbers(backups: &Path) -> std::io::Result<Vec<u64>> {
+    let mut numbers = Vec::new();
+    for entry in std::fs::read_dir(backups)? {
+        if let Some(number) = entry?
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u64>().ok())
+        {
+            numbers.push(number);
+        }
+    }
+    numbers.sort_unstable();
+    Ok(numbers)
+}
+
+/// the directories of the backups in `backups`, the newest first
+pub fn backups(backups: &Path) -> std::io::Result<Vec<PathBuf>> {
+    Ok(numbers(backups)?
+        .iter()
+        .rev()
+        .map(|number| backups.join(number.to_string()))
+        .collect())
+}
+
+/// copies the world directory into a new numbered directory in `backups` and deletes all but the `keep` newest backups
+pub fn back_up(world: &Path, backups: &Path, keep: usize) -> std::io::Result<()> {
+    if !world.exists() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(backups)?;
+    let mut numbers = numbers(backups)?;
+    let next = numbers.last().map_or(0, |last| last + 1);
+    copy_directory(world, &backups.join(next.to_string()))?;
+    numbers.push(next);
+    let old = numbers.len().saturating_sub(keep);
+    for number in &numbers[..old] {
+        std::fs::remove_dir_all(backups.join(number.to_string()))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn only_the_newest_backups_are_kept() {
+    let root = std::env::temp_dir().join(format!("backup_test_{}", std::process::id()));
+    let (world, backups) = (root.join("world"), root.join("backups"));
+    back_up(&world, &backups, 2).unwrap();
+    assert!(!backups.exists());
+    std::fs::create_dir_all(world.join("regions")).unwrap();
+    for generation in 0..4 {
+        std::fs::write(world.join("level.json"), generation.to_string()).unwrap();
+        std::fs::write(world.join("regions").join("r.0.0.tmp"), "torn").unwrap();
+        back_up(&world, &backups, 2).unwrap();
+    }
+    let mut kept: Vec<String> = std::fs::read_dir(&backups)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    kept.sort();
+    assert_eq!(kept, vec!["2", "3"]);
+    assert_eq!(
+        self::backups(&backups).unwrap(),
+        vec![backups.join("3"), backups.join("2")]
+    );
+    let level = std::fs::read_to_string(backups.join("3").join("level.json")).unwrap();
+    assert_eq!(level, "3");
+    assert!(!backups.join("3").join("regions").join("r.0.0.tmp").exists());
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,7 @@
+mod autosave;
+mod backup;
+mod region;
+
+pub use autosave::{WorldStorage, AUTOSAVE_INTERVAL};
+pub use backup::{back_up, backups, KEPT_BACKUPS};
+pub use region::{write_atomically, ChunkRecord};
@@ -0,0 +1,339 @@
+use crate::blocks::Block;
+use crate::chunk::{Chunk, CHUNK_SIZE};
+use crate::dir::Dir;
+use cgmath::Vector3;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// how many chunk columns a side of a region file has
+pub const REGION_CHUNKS: i32 = 8;
+const MAGIC: &[u8; 4] = b"CRGN";
+const VERSION: u32 = 1;
+/// the position, the length and the checksum in front of every record
+const RECORD_HEADER: usize = 4 * 5;
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(std::io::Error),
+    /// the record of the chunk at this position doesn't match its checksum
+    Corrupt(Vector3<i32>),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::Io(error) => write!(f, "{}", error),
+            StorageError::Corrupt(position) => write!(f, "chunk {:?} is corrupt", position),
+        }
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(error: std::io::Error) -> Self {
+        StorageError::Io(error)
+    }
+}
+
+/// the region a chunk is saved in
+pub fn region_of(chunk: Vector3<i32>) -> (i32, i32) {
+    (
+        chunk.x.div_euclid(REGION_CHUNKS),
+        chunk.z.div_euclid(REGION_CHUNKS),
+    )
+}
+
+pub fn region_path(directory: &Path, region: (i32, i32)) -> PathBuf {
+    directory
+        .join("regions")
+        .join(format!("r.{}.{}.bin", region.0, region.1))
+}
+
+/// FNV-1a, enough to notice a torn or flipped record
+pub fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811C_9DC5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// writes the file next to `path` first and then renames it, so `path` is either the old or the new file
+/// even when the game dies in the middle of writing
+pub fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temporary = path.with_extension("tmp");
+    let mut file = std::fs::File::create(&temporary)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&temporary, path)
+}
+
+/// the blocks of a chunk as they are saved, everything else of a chunk comes from the seed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkRecord {
+    pub position: Vector3<i32>,
+    pub blocks: Box<[[[Block; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]>,
+    pub facings: Box<[[[Dir; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]>,
+}
+
+impl ChunkRecord {
+    pub fn of(chunk: &Chunk) -> Self {
+        Self {
+            position: chunk.position,
+            blocks: chunk.blocks.clone(),
+            facings: chunk.facings.clone(),
+        }
+    }
+    /// a loaded chunk with these blocks, its climate is generated from `seed`
+    pub fn into_chunk(self, seed: u64) -> Chunk {
+        let mut chunk = Chunk::empty(self.position);
+        chunk.generate_climate(seed);
+        chunk.blocks = self.blocks;
+        chunk.facings = self.facings;
+        chunk.regenerate_mesh = true;
+        chunk.update_neighbours.west = Some(());
+        chunk.update_neighbours.east = Some(());
+        chunk.update_neighbours.north = Some(());
+        chunk.update_neighbours.south = Some(());
+        chunk.update_neighbours.up = Some(());
+        chunk.update_neighbours.down = Some(());
+        chunk
+    }
+    /// runs of equal blocks with equal facings, as the run length, the block and the facing
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut run: Option<(u16, u8, u8)> = None;
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    let block = self.blocks[x][y][z] as u8;
+                    let facing = self.facings[x][y][z] as u8;
+                    run = match run {
+                        Some((length, b, f)) if b == block && f == facing => {
+                            Some((length + 1, b, f))
+                        }
+                        Some((length, b, f)) => {
+                            bytes.extend_from_slice(&length.to_le_bytes());
+                            bytes.extend_from_slice(&[b, f]);
+                            Some((1, block, facing))
+                        }
+                        None => Some((1, block, facing)),
+                    };
+                }
+            }
+        }
+        if let Some((length, b, f)) = run {
+            bytes.extend_from_slice(&length.to_le_bytes());
+            bytes.extend_from_slice(&[b, f]);
+        }
+        bytes
+    }
+    fn decode(position: Vector3<i32>, bytes: &[u8]) -> Option<Self> {
+        let mut blocks = Box::new([[[Block::Empty; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]);
+        let mut facings = Box::new([[[Dir::Up; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE]);
+        let mut index = 0;
+        for run in bytes.chunks(4) {
+            if run.len() != 4 {
+                return None;
+            }
+            let length = u16::from_le_bytes([run[0], run[1]]) as usize;
+            let block = *Block::ALL.get(run[2] as usize)?;
+            let facing = Dir::iter().nth(run[3] as usize)?;
+            if index + length > CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE {
+                return None;
+            }
+            for i in index..index + length {
+                let (x, y, z) = (
+                    i / (CHUNK_SIZE * CHUNK_SIZE),
+                    i / CHUNK_SIZE % CHUNK_SIZE,
+                    i % CHUNK_SIZE,
+                );
+                blocks[x][y][z] = block;
+                facings[x][y][z] = facing;
+            }
+            index += length;
+        }
+        if index != CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE {
+            return None;
+        }
+        Some(Self {
+            position,
+            blocks,
+            facings,
+        })
+    }
+}
+
+/// the records of a region file, kept encoded
+#[derive(Debug, Default)]
+pub struct Region {
+    records: HashMap<Vector3<i32>, Vec<u8>>,
+    /// chunks whose records didn't match their checksum, they are generated again
+    corrupt: HashSet<Vector3<i32>>,
+    /// whether the file couldn't be read completely, a chunk it has no record of may have been lost
+    /// instead of never being saved
+    damaged: bool,
+}
+
+impl Region {
+    /// reads the region file at `path`, a missing file is an empty region,
+    /// a damaged file is copied aside before it is written again
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                let region = Self::from_bytes(&bytes);
+                if region.damaged {
+                    let aside = path.with_extension("bin.corrupt");
+                    eprintln!(
+                        "{} is damaged, a copy is kept as {}",
+                        path.display(),
+                        aside.display()
+                    );
+                    std::fs::copy(path, &aside)?;
+                }
+                Ok(region)
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+    /// every record that can still be read, anything else makes the region damaged
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut region = Self::default();
+        if bytes.len() < 8 || &bytes[0..4] != MAGIC {
+            region.damaged = true;
+            return region;
+        }
+        let word = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let mut at = 8;
+        while at < bytes.len() {
+            if at + RECORD_HEADER > bytes.len() {
+                region.damaged = true;
+                break;
+            }
+            let position = Vector3::new(word(at) as i32, word(at + 4) as i32, word(at + 8) as i32);
+            let length = word(at + 12) as usize;
+            let expected = word(at + 16);
+            let start = at + RECORD_HEADER;
+            if start + length > bytes.len() {
+                region.corrupt.insert(position);
+                region.damaged = true;
+                break;
+            }
+            let mut checked = bytes[at..at + 12].to_vec();
+            checked.extend_from_slice(&bytes[start..start + length]);
+            if checksum(&checked) == expected {
+                region
+                    .records
+                    .insert(position, bytes[start..start + length].to_vec());
+            } else {
+                // a wrong length would make the records after it unreadable too
+                region.corrupt.insert(position);
+                region.damaged = true;
+            }
+            at = start + length;
+        }
+        region
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        let mut positions: Vec<_> = self.records.keys().collect();
+        positions.sort_by_key(|p| (p.x, p.y, p.z));
+        for position in positions {
+            let payload = &self.records[position];
+            let mut checked = Vec::with_capacity(12 + payload.len());
+            for c in [position.x, position.y, position.z].iter() {
+                checked.extend_from_slice(&c.to_le_bytes());
+            }
+            checked.extend_from_slice(payload);
+            bytes.extend_from_slice(&checked[..12]);
+            bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&checksum(&checked).to_le_bytes());
+            bytes.extend_from_slice(payload);
+        }
+        bytes
+    }
+    pub fn insert(&mut self, record: &ChunkRecord) {
+        self.corrupt.remove(&record.position);
+        self.records.insert(record.position, record.encode());
+    }
+    /// the saved chunk at `position`, or nothing if it was never saved
+    pub fn get(&self, position: Vector3<i32>) -> Result<Option<ChunkRecord>, StorageError> {
+        if self.corrupt.contains(&position) {
+            return Err(StorageError::Corrupt(position));
+        }
+        match self.records.get(&position) {
+            Some(bytes) => ChunkRecord::decode(position, bytes)
+                .map(Some)
+                .ok_or(StorageError::Corrupt(position)),
+            None if self.damaged => Err(StorageError::Corrupt(position)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[test]
+fn records_survive_a_region_file_and_corruption_is_noticed() {
+    let mut chunk = Chunk::empty(Vector3::new(-3, 1, 9));
+    chunk.set_block_facing(Vector3::new(1, 2, 3), Block::Furnace, Dir::West);
+    chunk.set_block(Vector3::new(15, 15, 15), Block::Leaves);
+    let record = ChunkRecord::of(&chunk);
+    let mut other = ChunkRecord::of(&Chunk::empty(Vector3::new(-4, 0, 8)));
+    other.blocks[0][0][0] = Block::GoldOre;
+    let mut region = Region::default();
+    region.insert(&record);
+    region.insert(&other);
+    let bytes = region.to_bytes();
+    let read = Region::from_bytes(&bytes);
+    assert_eq!(read.get(record.position).unwrap(), Some(record.clone()));
+    assert_eq!(read.get(other.position).unwrap(), Some(other.clone()));
+    assert_eq!(read.get(Vector3::new(0, 0, 0)).unwrap(), None);
+
+    // a flipped bit only loses the chunk it is in
+    let mut flipped = bytes.clone();
+    let last = flipped.len() - 1;
+    flipped[last] ^= 0x10;
+    let read = Region::from_bytes(&flipped);
+    let positions = [other.position, record.position];
+    assert!(matches!(
+        read.get(positions[1]),
+        Err(StorageError::Corrupt(_))
+    ));
+    assert_eq!(read.get(positions[0]).unwrap(), Some(other.clone()));
+    // and a cut off file the ones at its end
+    let read = Region::from_bytes(&bytes[..bytes.len() - 5]);
+    assert!(read.get(positions[1]).is_err());
+    assert!(read.get(positions[0]).unwrap().is_some());
+    // chunks missing from a damaged file may have been lost, so they aren't reported as never saved
+    assert!(read.get(Vector3::new(0, 0, 0)).is_err());
+    // saving the chunk again repairs it
+    let mut read = read;
+    read.insert(&record);
+    assert_eq!(read.get(record.position).unwrap(), Some(record.clone()));
+
+    // a wrong length loses track of every record after it
+    let mut long = bytes.clone();
+    long[8 + 12..8 + 16].copy_from_slice(&u32::MAX.to_le_bytes());
+    let read = Region::from_bytes(&long);
+    assert!(read.get(positions[0]).is_err());
+    assert!(read.get(positions[1]).is_err());
+    // and so does a wrong magic number
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    let read = Region::from_bytes(&magic);
+    assert!(read.get(positions[0]).is_err());
+    assert!(read.get(Vector3::new(0, 0, 0)).is_err());
+
+    // the damaged file is kept before it is written again
+    let directory = std::env::temp_dir().join(format!("region_test_{}", std::process::id()));
+    let path = region_path(&directory, (0, 0));
+    write_atomically(&path, &magic).unwrap();
+    Region::load(&path).unwrap();
+    let aside = std::fs::read(path.with_extension("bin.corrupt")).unwrap();
+    assert_eq!(aside, magic);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
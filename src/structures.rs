@@ -0,0 +1,252 @@
+use crate::biomes::Biome;
+use crate::blocks::Block;
+use crate::chunk::{Chunk, CHUNK_SIZE};
+use crate::chunk_map::ChunkMap;
+use crate::dir::Dir;
+use crate::erosion::HeightCache;
+use crate::noise::{hash, random_2d};
+use crate::schematic::ruin;
+use crate::selection::BlockChange;
+use crate::world_gen::{intact_surface, GeneratorSettings};
+use cgmath::Vector3;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const STRUCTURE_SEED: u64 = 0x57C0;
+
+/// something bigger than a block that generation puts on the ground
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Structure {
+    Tree,
+    Boulder,
+    /// the ruin schematic, turned to a random side
+    Ruin,
+}
+
+impl Structure {
+    /// the blocks of the structure standing on the ground at `base`, in world coordinates,
+    /// `rng` is seeded by where it stands so it always looks the same
+    fn blocks(self, base: Vector3<i32>, rng: &mut StdRng) -> Vec<BlockChange> {
+        let mut blocks = Vec::new();
+        match self {
+            Structure::Tree => {
+                let trunk = rng.gen_range(4, 7);
+                for y in trunk - 3..trunk + 1 {
+                    // wide at the bottom and narrow at the top
+                    let radius = if y < trunk - 1 { 2 } else { 1 };
+                    for x in -radius..radius + 1 {
+                        for z in -radius..radius + 1 {
+                            let corner = x * x + z * z == radius * radius * 2;
+                            if corner && (y == trunk || rng.gen_range(0, 2) == 0) {
+                                continue;
+                            }
+                            blocks.push((base + Vector3::new(x, y, z), Block::Leaves, Dir::Up));
+                        }
+                    }
+                }
+                for y in 0..trunk {
+                    blocks.push((base + Vector3::new(0, y, 0), Block::Log, Dir::Up));
+                }
+            }
+            Structure::Boulder => {
+                let radius: i32 = rng.gen_range(1, 3);
+                for x in -radius..radius + 1 {
+                    for y in -radius..radius + 1 {
+                        for z in -radius..radius + 1 {
+                            if x * x + y * y + z * z <= radius * radius {
+                                blocks.push((base + Vector3::new(x, y, z), Block::Stone, Dir::Up));
+                            }
+                        }
+                    }
+                }
+            }
+            Structure::Ruin => {
+                let mut ruin = ruin().clone();
+                for _ in 0..rng.gen_range(0, 4) {
+                    ruin = ruin.rotated();
+                }
+                // centered on the column, the floor lies on the ground
+                let corner = base - Vector3::new(ruin.size.x / 2, 0, ruin.size.z / 2);
+                for x in 0..ruin.size.x {
+                    for y in 0..ruin.size.y {
+                        for z in 0..ruin.size.z {
+                            let position = Vector3::new(x, y, z);
+                            let (block, facing) = ruin.get(position);
+                            if block != Block::Empty {
+                                blocks.push((corner + position, block, facing));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        blocks
+    }
+}
+
+/// which structure block wins where two structures meet, the same one in whatever order they are placed
+fn rank(block: Block) -> u8 {
+    match block {
+        Block::Leaves => 1,
+        Block::Log => 2,
+        Block::Stone => 3,
+        _ => 0,
+    }
+}
+
+/// puts a structure block into a chunk, structures only grow into air, plants and weaker structure blocks
+pub fn place(chunk: &mut Chunk, position: Vector3<u16>, block: Block, facing: Dir) {
+    let existing = chunk.get_block(position);
+    let replaceable = matches!(existing, Block::Empty | Block::TallGrass);
+    let weaker = rank(existing) != 0 && rank(existing) < rank(block);
+    if replaceable || weaker {
+        chunk.set_block_facing(position, block, facing);
+    }
+}
+
+/// how far structure blocks reach sideways from the column a structure stands on,
+/// trees and boulders reach 2 blocks and the ruin half its width
+fn structure_reach() -> i32 {
+    let ruin = ruin();
+    (ruin.size.x.max(ruin.size.z) / 2).max(2)
+}
+
+/// the highest chance of any biome that a column gets a structure, columns rolling above it get none
+fn structure_chance_bound() -> f32 {
+    [Biome::Plains, Biome::Desert, Biome::Ocean, Biome::Mountains]
+        .iter()
+        .map(|biome| biome.structures().iter().map(|(_, chance)| chance).sum())
+        .fold(0.0, f32::max)
+}
+
+/// the blocks of the structures that reach into the chunk at `position`, in world coordinates,
+/// including structures standing in the chunks around it, so no chunk has to wait for its neighbours
+pub fn structures_in_chunk(
+    seed: u64,
+    settings: &GeneratorSettings,
+    heights: &mut HeightCache,
+    position: Vector3<i32>,
+) -> Vec<BlockChange> {
+    let size = CHUNK_SIZE as i32;
+    let bound = structure_chance_bound();
+    let reach = structure_reach();
+    let mut blocks = Vec::new();
+    for x in position.x * size - reach..(position.x + 1) * size + reach {
+        for z in position.z * size - reach..(position.z + 1) * size + reach {
+            let roll = random_2d(seed ^ STRUCTURE_SEED, x, z);
+            if roll >= bound {
+                continue;
+            }
+            let (biome, height) = match intact_surface(seed, settings, heights, x, z) {
+                Some(surface) => surface,
+                None => continue,
+            };
+            let mut chance = 0.0;
+            for (structure, structure_chance) in biome.structures() {
+                chance += structure_chance;
+                if roll < chance {
+                    let mut rng = StdRng::seed_from_u64(hash(seed ^ STRUCTURE_SEED, x, height, z));
+                    let base = Vector3::new(x, height, z);
+                    blocks.extend(structure.blocks(base, &mut rng).into_iter().filter(
+                        |(coords, _, _)| {
+                            ChunkMap::coords_to_chunk_coords_and_block(*coords).0 == position
+                        },
+                    ));
+                    break;
+                }
+            }
+        }
+    }
+    blocks
+}
+
+/// places the parts of every structure that reach into a freshly generated chunk
+pub fn add_structures(
+    seed: u64,
+    settings: &GeneratorSettings,
+    heights: &mut HeightCache,
+    chunk: &mut Chunk,
+) {
+    for (coords, block, facing) in structures_in_chunk(seed, settings, heights, chunk.position) {
+        let position = ChunkMap::coords_to_chunk_coords_and_block(coords).1;
+        place(chunk, position, block, facing);
+    }
+}
+
+#[test]
+fn structures_do_not_depend_on_the_order_chunks_are_generated_in() {
+    use crate::world_gen::WORLD_HEIGHT;
+    use std::collections::HashMap;
+    fn generate_area(seed: u64, columns: &[(i32, i32)]) -> HashMap<Vector3<i32>, Chunk> {
+        let settings = GeneratorSettings::default();
+        let mut heights = HeightCache::default();
+        let mut chunks: HashMap<Vector3<i32>, Chunk> = HashMap::new();
+        for &(x, z) in columns {
+            for y in 0..WORLD_HEIGHT {
+                let position = Vector3::new(x, y, z);
+                let mut chunk =
+                    crate::world_gen::generate_chunk(seed, &settings, &mut heights, position);
+                add_structures(seed, &settings, &mut heights, &mut chunk);
+                chunks.insert(position, chunk);
+            }
+        }
+        chunks
+    }
+    let seed = 21;
+    let mut columns = Vec::new();
+    for x in -1..2 {
+        for z in -1..2 {
+            columns.push((x, z));
+        }
+    }
+    let forwards = generate_area(seed, &columns);
+    columns.reverse();
+    let backwards = generate_area(seed, &columns);
+    // only the middle, its neighbours are never generated
+    let alone = generate_area(seed, &[(0, 0)]);
+    let mut structure_blocks = 0;
+    for (position, chunk) in forwards.iter() {
+        assert_eq!(chunk.blocks, backwards[position].blocks);
+        if let Some(alone) = alone.get(position) {
+            assert_eq!(chunk.blocks, alone.blocks);
+        }
+        for x in chunk.blocks.iter() {
+            for y in x.iter() {
+                structure_blocks += y.iter().filter(|b| **b == Block::Leaves).count();
+            }
+        }
+    }
+    assert!(structure_blocks > 0);
+
+    // every structure block is there, nothing is cut off at the border of the area
+    let settings = GeneratorSettings::default();
+    let mut heights = HeightCache::default();
+    for (position, chunk) in forwards.iter() {
+        for (coords, _, _) in structures_in_chunk(seed, &settings, &mut heights, *position) {
+            let (in_chunk, block) = ChunkMap::coords_to_chunk_coords_and_block(coords);
+            assert_eq!(in_chunk, *position);
+            assert_ne!(chunk.get_block(block), Block::Empty);
+        }
+    }
+}
+
+#[test]
+fn structures_stay_within_their_reach() {
+    let reach = structure_reach();
+    let base = Vector3::new(0, 10, 0);
+    for &structure in &[Structure::Tree, Structure::Boulder, Structure::Ruin] {
+        for seed in 0..8 {
+            let blocks = structure.blocks(base, &mut StdRng::seed_from_u64(seed));
+            assert!(blocks
+                .iter()
+                .all(|(coords, _, _)| coords.x.abs() <= reach && coords.z.abs() <= reach));
+        }
+    }
+    let mut chunk = Chunk::empty(Vector3::new(0, 0, 0));
+    place(
+        &mut chunk,
+        Vector3::new(1, 2, 3),
+        Block::StoneStairs,
+        Dir::East,
+    );
+    assert_eq!(chunk.get_facing(Vector3::new(1, 2, 3)), Dir::East);
+}
@@ -0,0 +1,34 @@
+use crate::chunk::Chunk;
+use crate::level::{Level, LevelData, LEVEL_FILE};
+use crate::resources::DeltaTime;
+use crate::storage::{ChunkRecord, WorldStorage, AUTOSAVE_INTERVAL};
+use specs::{Join, Read, System, WriteExpect, WriteStorage};
+
+/// saves the chunks that changed and the level every AUTOSAVE_INTERVAL seconds
+pub struct Autosave;
+impl<'a> System<'a> for Autosave {
+    type SystemData = (
+        Read<'a, DeltaTime>,
+        WriteExpect<'a, WorldStorage>,
+        WriteStorage<'a, Chunk>,
+        LevelData<'a>,
+    );
+
+    fn run(&mut self, (dt, mut storage, mut chunks, level): Self::SystemData) {
+        storage.since_save += dt.0;
+        if storage.since_save < AUTOSAVE_INTERVAL {
+            return;
+        }
+        storage.since_save = 0.0;
+        let mut records = Vec::new();
+        for chunk in (&mut chunks).join().filter(|chunk| chunk.unsaved) {
+            records.push(ChunkRecord::of(chunk));
+            chunk.unsaved = false;
+        }
+        storage.save(records);
+        // with the chunks, so the players' inventories always match the blocks they broke and placed
+        if let Err(error) = Level::of(&level).save_after_chunks(&storage) {
+            eprintln!("could not save {}: {}", LEVEL_FILE, error);
+        }
+    }
+}
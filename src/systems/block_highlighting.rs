@@ -1,16 +1,37 @@
 use cgmath::Vector3;
-use specs::{Join, ReadStorage, System, WriteStorage};
+use specs::{Entities, Join, ReadExpect, ReadStorage, System, WriteStorage};
 
-use crate::components::{BlockHighlightCube, LookedAt, Position};
+use crate::components::{
+    BlockHighlightCube, BreakProgress, CrackOverlay, LookedAt, Position, UvMesh,
+};
+use crate::resources::{CrackMeshes, CRACK_STAGES};
 
 pub struct BlockHighlighting;
 impl<'a> System<'a> for BlockHighlighting {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         ReadStorage<'a, LookedAt>,
         ReadStorage<'a, BlockHighlightCube>,
         WriteStorage<'a, Position>,
+        Entities<'a>,
+        ReadStorage<'a, CrackOverlay>,
+        ReadStorage<'a, BreakProgress>,
+        WriteStorage<'a, UvMesh>,
+        ReadExpect<'a, CrackMeshes>,
     );
-    fn run(&mut self, (looked_at_positions, cubes, mut positions): Self::SystemData) {
+    fn run(
+        &mut self,
+        (
+            looked_at_positions,
+            cubes,
+            mut positions,
+            entities,
+            overlays,
+            progresses,
+            mut meshes,
+            crack_meshes,
+        ): Self::SystemData,
+    ) {
         for (cube, position) in (&cubes, &mut positions).join() {
             if let Some(target) = looked_at_positions.get(cube.0) {
                 position.0 = Vector3::new(
@@ -20,5 +41,25 @@ impl<'a> System<'a> for BlockHighlighting {
                 );
             }
         }
+        for (entity, overlay, position) in (&entities, &overlays, &mut positions).join() {
+            match progresses.get(overlay.0) {
+                Some(progress) => {
+                    position.0 = Vector3::new(
+                        progress.coords.x as f32,
+                        progress.coords.y as f32,
+                        progress.coords.z as f32,
+                    );
+                    let stage =
+                        ((progress.progress * CRACK_STAGES as f32) as usize).min(CRACK_STAGES - 1);
+                    meshes
+                        .insert(entity, UvMesh(crack_meshes.0[stage].clone()))
+                        .unwrap();
+                }
+                // without a mesh the overlay isn't rendered
+                None => {
+                    meshes.remove(entity);
+                }
+            }
+        }
     }
 }
@@ -1,10 +1,21 @@
 use std::sync::{Arc, Mutex};
 
-use crate::{blocks::Block, components::LookedAt};
+use crate::history::EditHistory;
+use crate::items::{DroppedItem, Inventory, Item, ItemStack, DROPPED_ITEM_SIZE};
+use crate::resources::{DeltaTime, GameMode, Hotbar};
+use crate::selection::Selection;
+use crate::world_editor::WorldEditor;
+use crate::{block_tick::BlockTicks, blocks::Block, components::LookedAt};
 use crate::{chunk::Chunk, chunk_map::ChunkMap};
-use crate::{components::Player, resources::SoundPlayer};
-use simple_winit::input::Input;
-use specs::{Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
+use crate::{
+    components::{BreakProgress, Player, Position, Scale, Velocity},
+    resources::SoundPlayer,
+};
+use cgmath::Vector3;
+use simple_winit::input::{Input, MouseButton};
+use specs::{
+    Builder, Entities, Join, LazyUpdate, Read, ReadExpect, ReadStorage, System, Write, WriteStorage,
+};
 
 pub struct BreakBlocks;
 
@@ -17,47 +28,117 @@ impl<'a> System<'a> for BreakBlocks {
         ReadStorage<'a, LookedAt>,
         ReadExpect<'a, Arc<Mutex<Input>>>,
         ReadExpect<'a, SoundPlayer>,
+        Write<'a, BlockTicks>,
+        ReadExpect<'a, GameMode>,
+        Entities<'a>,
+        Read<'a, LazyUpdate>,
+        Read<'a, DeltaTime>,
+        WriteStorage<'a, BreakProgress>,
+        ReadStorage<'a, Inventory>,
+        Write<'a, EditHistory>,
+        Read<'a, Selection>,
+        ReadExpect<'a, Hotbar>,
     );
 
     fn run(
         &mut self,
-        (chunk_map, mut chunks, players, looked_at, input, player): Self::SystemData,
+        (
+            chunk_map,
+            mut chunks,
+            players,
+            looked_at,
+            input,
+            player,
+            mut block_ticks,
+            game_mode,
+            entities,
+            lazy,
+            delta_time,
+            mut progresses,
+            inventories,
+            mut history,
+            selection,
+            hotbar,
+        ): Self::SystemData,
     ) {
         let input = input.lock().unwrap();
-        for (_, looked_at) in (&players, &looked_at).join() {
-            if input.button_pressed(simple_winit::input::MouseButton::Left) {
-                if let Some(chunk) = chunk_map.get_chunk_from_coords(looked_at.coords) {
-                    let block_coord =
-                        ChunkMap::coords_to_chunk_coords_and_block(looked_at.coords).1;
-                    let chunk = chunks.get_mut(chunk).unwrap();
+        // with the selection wand clicks mark corners
+        let pressed = input.button_pressed(MouseButton::Left) && !selection.wand;
+        let held = input.button_held(MouseButton::Left) && !selection.wand;
+        for (entity, _) in (&entities, &players).join() {
+            let coords = match looked_at.get(entity) {
+                Some(looked_at) if pressed || held => looked_at.coords,
+                _ => {
+                    progresses.remove(entity);
+                    continue;
+                }
+            };
+            let mut editor = WorldEditor::new(&chunk_map, &mut chunks, &mut block_ticks);
+            let block = match editor.get_block(coords) {
+                Some(block) => block,
+                None => continue,
+            };
 
-                    match chunk.get_block(block_coord) {
-                        Block::Empty => {}
-                        Block::Dirt => {
-                            let sand = ton::PlayableSfxr::load_from_json(
-                                &std::fs::read_to_string("./dirt.json").unwrap(),
-                            )
-                            .unwrap();
-                            player.play(sand).detach();
-                        }
-                        Block::Stone => {}
-                        Block::Sand => {
-                            let sand = ton::PlayableSfxr::load_from_json(
-                                &std::fs::read_to_string("./sand.json").unwrap(),
+            let finished = match *game_mode {
+                GameMode::Creative => pressed,
+                GameMode::Survival => {
+                    // looking at another block starts over
+                    if progresses.get(entity).map(|p| p.coords) != Some(coords) {
+                        progresses
+                            .insert(
+                                entity,
+                                BreakProgress {
+                                    coords,
+                                    progress: 0.0,
+                                },
                             )
                             .unwrap();
-                            player.play(sand).detach();
-                        }
-                        Block::Water => {}
-                        Block::Grass => {
-                            let sand = ton::PlayableSfxr::load_from_json(
-                                &std::fs::read_to_string("./dirt.json").unwrap(),
-                            )
-                            .unwrap();
-                            player.play(sand).detach();
-                        }
                     }
-                    chunk.set_block(block_coord, Block::Empty);
+                    let progress = progresses.get_mut(entity).unwrap();
+                    let hardness = block.hardness();
+                    if hardness > 0.0 {
+                        let speed = inventories.get(entity).map_or(1.0, |inventory| {
+                            inventory.break_speed(hotbar.selected, block)
+                        });
+                        progress.progress += delta_time.0 * speed / hardness;
+                    } else {
+                        progress.progress = 1.0;
+                    }
+                    progress.progress >= 1.0
+                }
+            };
+            if !finished {
+                continue;
+            }
+            progresses.remove(entity);
+
+            if let Some(sound) = block.sound() {
+                let sound =
+                    ton::PlayableSfxr::load_from_json(&std::fs::read_to_string(sound).unwrap())
+                        .unwrap();
+                player.play(sound).detach();
+            }
+            editor.set_block(coords, Block::Empty);
+            let edits = editor.finish();
+            // undoing is only possible in creative mode, so survival edits aren't remembered
+            if *game_mode == GameMode::Creative {
+                history.record(edits);
+            }
+            if *game_mode == GameMode::Survival {
+                if let Some(item) = Item::dropped_by(block) {
+                    let center = Vector3::new(
+                        coords.x as f32 + 0.5,
+                        coords.y as f32 + 0.5,
+                        coords.z as f32 + 0.5,
+                    );
+                    lazy.create_entity(&entities)
+                        .with(DroppedItem(ItemStack::new(item, 1)))
+                        .with(Position(
+                            center - Vector3::new(1.0, 1.0, 1.0) * DROPPED_ITEM_SIZE / 2.0,
+                        ))
+                        .with(Scale(Vector3::new(1.0, 1.0, 1.0) * DROPPED_ITEM_SIZE))
+                        .with(Velocity(Vector3::new(0.0, 0.0, 0.0)))
+                        .build();
                 }
             }
         }
@@ -1,9 +1,10 @@
-use crate::blocks::{Atlas, Block};
+use crate::block_model::{covers_side, model_faces};
+use crate::blocks::{Atlas, Block, RenderLayer, UV_GRID};
 use crate::chunk::{Chunk, CHUNK_SIZE};
+use crate::chunk_map::ChunkMap;
 use crate::chunk_middle_ware::ChunkVertex;
 use crate::components::ChunkMesh;
 use crate::dir::Dir;
-use crate::{blocks::Side, chunk_map::ChunkMap};
 use cgmath::Vector3;
 use specs::{Entities, Join, Read, ReadExpect, System, WriteStorage};
 
@@ -29,7 +30,9 @@ impl<'a> System<'a> for ChunkMeshGeneration {
                     }
                     counter -= 1;
                     let vertices = &mut mesh.0.vertices;
-                    vertices.clear();
+                    for layer in vertices.iter_mut() {
+                        layer.clear();
+                    }
 
                     let mut north = None;
                     if let Some(e) = chunk_map.get_chunk(chunk.position + Vector3::new(0, 0, 1)) {
@@ -70,71 +73,17 @@ impl<'a> System<'a> for ChunkMeshGeneration {
                         }
                     }
 
+                    let neighbours = [north, south, east, west, up, down];
                     for x in 0..CHUNK_SIZE {
                         for y in 0..CHUNK_SIZE {
                             for z in 0..CHUNK_SIZE {
-                                for dir in Dir::iter() {
-                                    let mut discard =
-                                        check_discard(dir, x as u16, y as u16, z as u16, &chunk);
-
-                                    if dir == Dir::East {
-                                        if let Some(east) = east {
-                                            if x == CHUNK_SIZE - 1 {
-                                                discard = east.blocks[0][y as usize][z as usize]
-                                                    != Block::Empty;
-                                            }
-                                        }
-                                    } else if dir == Dir::West {
-                                        if let Some(west) = west {
-                                            if x == 0 {
-                                                discard = west.blocks[CHUNK_SIZE as usize - 1]
-                                                    [y as usize]
-                                                    [z as usize]
-                                                    != Block::Empty;
-                                            }
-                                        }
-                                    } else if dir == Dir::North {
-                                        if let Some(north) = north {
-                                            if z == CHUNK_SIZE - 1 {
-                                                discard = north.blocks[x as usize][y as usize][0]
-                                                    != Block::Empty;
-                                            }
-                                        }
-                                    } else if dir == Dir::South {
-                                        if let Some(south) = south {
-                                            if z == 0 {
-                                                discard = south.blocks[x as usize][y as usize]
-                                                    [CHUNK_SIZE as usize - 1]
-                                                    != Block::Empty;
-                                            }
-                                        }
-                                    } else if dir == Dir::Up {
-                                        if let Some(up) = up {
-                                            if y == CHUNK_SIZE - 1 {
-                                                discard = up.blocks[x as usize][0][z as usize]
-                                                    != Block::Empty
-                                            }
-                                        }
-                                    } else if dir == Dir::Down {
-                                        if let Some(down) = down {
-                                            if y == 0 {
-                                                discard = down.blocks[x as usize]
-                                                    [CHUNK_SIZE as usize - 1]
-                                                    [z as usize]
-                                                    != Block::Empty;
-                                            }
-                                        }
-                                    }
-                                    if !discard {
-                                        add_face(
-                                            &atlas,
-                                            chunk,
-                                            [x as u8, y as u8, z as u8],
-                                            dir,
-                                            vertices,
-                                        );
-                                    }
-                                }
+                                add_block(
+                                    &atlas,
+                                    chunk,
+                                    &neighbours,
+                                    Vector3::new(x as u8, y as u8, z as u8),
+                                    vertices,
+                                );
                             }
                         }
                     }
@@ -147,151 +96,109 @@ impl<'a> System<'a> for ChunkMeshGeneration {
     }
 }
 
-fn check_discard(dir: Dir, x: u16, y: u16, z: u16, chunk: &Chunk) -> bool {
-    match dir {
-        Dir::North => {
-            if z < CHUNK_SIZE as u16 - 1
-                && chunk.blocks[x as usize][y as usize][z as usize + 1] != Block::Empty
-            {
-                return true;
-            }
-        }
-        Dir::South => {
-            if z > 0 && chunk.blocks[x as usize][y as usize][z as usize - 1] != Block::Empty {
-                return true;
-            }
-        }
-        Dir::East => {
-            if x < CHUNK_SIZE as u16 - 1
-                && chunk.blocks[x as usize + 1][y as usize][z as usize] != Block::Empty
-            {
-                return true;
-            }
-        }
-        Dir::West => {
-            if x > 0 && chunk.blocks[x as usize - 1][y as usize][z as usize] != Block::Empty {
-                return true;
-            }
-        }
-        Dir::Up => {
-            if y < CHUNK_SIZE as u16 - 1
-                && chunk.blocks[x as usize][y as usize + 1][z as usize] != Block::Empty
-            {
-                return true;
-            }
-        }
-        Dir::Down => {
-            if y > 0 && chunk.blocks[x as usize][y as usize - 1][z as usize] != Block::Empty {
-                return true;
-            }
+/// whether the block next to `position` towards `dir` covers its whole side facing it,
+/// `neighbours` are the chunks around `chunk` in the order of Dir,
+/// blocks that can be seen through only hide faces of their own kind, like water next to water
+fn is_hidden(
+    chunk: &Chunk,
+    neighbours: &[Option<&Chunk>; 6],
+    position: Vector3<u8>,
+    dir: Dir,
+    block: Block,
+) -> bool {
+    let size = CHUNK_SIZE as i32;
+    let next = position.cast::<i32>().unwrap() + Vector3::from(dir);
+    let inside = |c: i32| c >= 0 && c < size;
+    let (chunk, next) = if inside(next.x) && inside(next.y) && inside(next.z) {
+        (chunk, next)
+    } else {
+        match neighbours[dir as usize] {
+            Some(neighbour) => (neighbour, next.map(|c| c.rem_euclid(size))),
+            // faces at the border of the loaded world stay visible
+            None => return false,
         }
-    }
-    false
+    };
+    let next = next.cast::<u16>().unwrap();
+    let next_block = chunk.get_block(next);
+    next_block != Block::Empty
+        && (next_block.render_layer() == RenderLayer::Opaque || next_block == block)
+        && covers_side(
+            next_block.shape(),
+            next_block.orientation(),
+            chunk.get_facing(next),
+            dir.opposite(),
+        )
 }
 
-fn add_face(
+fn add_block(
     atlas: &Atlas,
     chunk: &Chunk,
-    position: [u8; 3],
-    face: Dir,
-    vertices: &mut Vec<ChunkVertex>,
+    neighbours: &[Option<&Chunk>; 6],
+    position: Vector3<u8>,
+    vertices: &mut [Vec<ChunkVertex>; 3],
 ) {
-    let mut add = |o, normal, uv_index| {
-        vertices.push(ChunkVertex::new(
-            Vector3::new(position[0], position[1], position[2]) + o,
-            normal,
-            uv_index,
-        ))
-    };
-
-    let uv_index =
-        chunk.blocks[position[0] as usize][position[1] as usize][position[2] as usize] as u16;
-
-    let uv = if uv_index > 0 {
-        /*Some(atlas.uvs_of_block(
-            uv_index as usize,
-            match face {
-                Dir::North | Dir::South | Dir::East | Dir::West => Side::Side,
-                Dir::Up => Side::Top,
-                Dir::Down => Side::Base,
-            },
-        ))*/
-        let indices = atlas.uvs_of_block_index(
-            uv_index as usize,
-            match face {
-                Dir::North | Dir::South | Dir::East | Dir::West => Side::Side,
-                Dir::Up => Side::Top,
-                Dir::Down => Side::Base,
-            },
-        );
-        //let mut uvs = [Vector2::new(0.0, 0.0); 4];
-        let mut uvs = [0; 4];
-        for i in 0..4 {
-            //uvs[i] = atlas.all_uvs[indices[i] as usize].into();
-            uvs[i] = indices[i] as u32;
-        }
-        Some(uvs)
-    } else {
-        None
-    };
-
-    if let Some(uv) = uv {
-        #[rustfmt::skip]
-        match face {
-            Dir::North => {
-                add(Vector3::new(0, 0, 1), Dir::North, uv[2]);
-                add(Vector3::new(1, 0, 1), Dir::North, uv[3]);
-                add(Vector3::new(1, 1, 1), Dir::North, uv[1]);
-
-                add(Vector3::new(0, 0, 1), Dir::North, uv[2]);
-                add(Vector3::new(1, 1, 1), Dir::North, uv[1]);
-                add(Vector3::new(0, 1, 1), Dir::North, uv[0]);
-            }
-            Dir::South => {
-                add(Vector3::new(0, 0, 0), Dir::South, uv[2]);
-                add(Vector3::new(1, 1, 0), Dir::South, uv[1]);
-                add(Vector3::new(1, 0, 0), Dir::South, uv[3]);
-
-                add(Vector3::new(0, 0, 0), Dir::South, uv[2]);
-                add(Vector3::new(0, 1, 0), Dir::South, uv[0]);
-                add(Vector3::new(1, 1, 0), Dir::South, uv[1]);
-            }
-            Dir::East => {
-                add(Vector3::new(1, 0, 0), Dir::East, uv[2]);
-                add(Vector3::new(1, 1, 1), Dir::East, uv[1]);
-                add(Vector3::new(1, 0, 1), Dir::East, uv[3]);
-
-                add(Vector3::new(1, 0, 0), Dir::East, uv[2]);
-                add(Vector3::new(1, 1, 0), Dir::East, uv[0]);
-                add(Vector3::new(1, 1, 1), Dir::East, uv[1]);
-            }
-            Dir::West => {
-                add(Vector3::new(0, 0, 0), Dir::West, uv[2]);
-                add(Vector3::new(0, 0, 1), Dir::West, uv[3]);
-                add(Vector3::new(0, 1, 1), Dir::West, uv[1]);
-
-                add(Vector3::new(0, 0, 0), Dir::West, uv[2]);
-                add(Vector3::new(0, 1, 1), Dir::West, uv[1]);
-                add(Vector3::new(0, 1, 0), Dir::West, uv[0]);
-            }
-            Dir::Up => {
-                add(Vector3::new(0, 1, 0), Dir::Up, uv[0]);
-                add(Vector3::new(1, 1, 1), Dir::Up, uv[3]);
-                add(Vector3::new(1, 1, 0), Dir::Up, uv[2]);
-
-                add(Vector3::new(0, 1, 0), Dir::Up, uv[0]);
-                add(Vector3::new(0, 1, 1), Dir::Up, uv[1]);
-                add(Vector3::new(1, 1, 1), Dir::Up, uv[3]);
-            }
-            Dir::Down => {
-                add(Vector3::new(0, 0, 0), Dir::Down, uv[0]);
-                add(Vector3::new(1, 0, 0), Dir::Down, uv[2]);
-                add(Vector3::new(1, 0, 1), Dir::Down, uv[3]);
-
-                add(Vector3::new(0, 0, 0), Dir::Down, uv[0]);
-                add(Vector3::new(1, 0, 1), Dir::Down, uv[3]);
-                add(Vector3::new(0, 0, 1), Dir::Down, uv[1]);
+    let block = chunk.get_block(position.cast::<u16>().unwrap());
+    if block == Block::Empty {
+        return;
+    }
+    let facing = chunk.get_facing(position.cast::<u16>().unwrap());
+    for face in model_faces(block.shape(), block.orientation(), facing) {
+        if let Some(dir) = face.cull {
+            if is_hidden(chunk, neighbours, position, dir, block) {
+                continue;
             }
         }
+        // turned blocks show another of their sides here, rotated along with the block
+        let uv = atlas.uvs_of_block_index(block as usize, face.side);
+        let tint = if block.is_tinted(face.side) {
+            chunk.grass_colors[position.x as usize][position.z as usize]
+        } else {
+            [255; 3]
+        };
+        for (offset, [u, v]) in face.vertices.iter() {
+            // chunk vertices are in half blocks
+            let vertex = ChunkVertex::new(
+                position * 2 + offset,
+                face.normal,
+                uv[v * UV_GRID + u] as u32,
+            );
+            vertices[block.render_layer() as usize].push(vertex.with_tint(tint));
+        }
     }
 }
+
+#[test]
+fn translucent_blocks_only_hide_their_own_kind() {
+    let mut chunk = Chunk::empty(Vector3::new(0, 0, 0));
+    chunk.set_block(Vector3::new(1, 1, 1), Block::Water);
+    chunk.set_block(Vector3::new(2, 1, 1), Block::Water);
+    chunk.set_block(Vector3::new(1, 0, 1), Block::Stone);
+    let hidden = |position, dir, block| is_hidden(&chunk, &[None; 6], position, dir, block);
+    let water = Vector3::new(1, 1, 1);
+    assert!(hidden(water, Dir::East, Block::Water));
+    assert!(hidden(water, Dir::Down, Block::Water));
+    // the stone below the water can be seen through it
+    let stone = Vector3::new(1, 0, 1);
+    assert!(!hidden(stone, Dir::Up, Block::Stone));
+    // nothing is known about the chunks around
+    assert!(!hidden(stone, Dir::Down, Block::Stone));
+}
+#[test]
+fn water_only_shows_faces_it_can_be_seen_through() {
+    let mut chunk = Chunk::empty(Vector3::new(0, 0, 0));
+    chunk.set_block(Vector3::new(1, 1, 1), Block::Water);
+    chunk.set_block(Vector3::new(1, 2, 1), Block::Water);
+    chunk.set_block(Vector3::new(2, 1, 1), Block::Leaves);
+    chunk.set_block(Vector3::new(0, 1, 1), Block::StoneSlab);
+    chunk.set_block(Vector3::new(1, 1, 0), Block::Dirt);
+    let hidden = |position, dir, block| is_hidden(&chunk, &[None; 6], position, dir, block);
+    let water = Vector3::new(1, 1, 1);
+    // air above the top water and leaves next to it can be seen through
+    assert!(!hidden(Vector3::new(1, 2, 1), Dir::Up, Block::Water));
+    assert!(!hidden(water, Dir::East, Block::Water));
+    // a slab only covers the lower half of the side
+    assert!(!hidden(water, Dir::West, Block::Water));
+    assert!(hidden(water, Dir::South, Block::Water));
+    // and the dirt is seen through the water
+    assert!(!hidden(Vector3::new(1, 1, 0), Dir::North, Block::Dirt));
+}
@@ -0,0 +1,111 @@
+use crate::block_model::collision_boxes;
+use crate::components::{Player, Position, UvMesh, Velocity};
+use crate::items::{DroppedItem, Inventory, Item, DROPPED_ITEM_SIZE};
+use crate::resources::{BlockMeshes, DeltaTime};
+use crate::state::{GRAVITY, TERMINAL_VELOCITY};
+use crate::{chunk::Chunk, chunk_map::ChunkMap};
+use cgmath::{InnerSpace, Vector3};
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
+
+/// how close a player has to get to a dropped item to pick it up
+const PICKUP_DISTANCE: f32 = 1.5;
+
+pub struct DroppedItems;
+impl<'a> System<'a> for DroppedItems {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, DeltaTime>,
+        Read<'a, ChunkMap>,
+        ReadStorage<'a, Chunk>,
+        WriteStorage<'a, DroppedItem>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Velocity>,
+        WriteStorage<'a, UvMesh>,
+        ReadExpect<'a, BlockMeshes>,
+        ReadStorage<'a, Player>,
+        WriteStorage<'a, Inventory>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            delta_time,
+            chunk_map,
+            chunks,
+            mut dropped_items,
+            mut positions,
+            mut velocities,
+            mut meshes,
+            block_meshes,
+            players,
+            mut inventories,
+        ): Self::SystemData,
+    ) {
+        let dt = delta_time.0;
+        let half = Vector3::new(1.0, 1.0, 1.0) * DROPPED_ITEM_SIZE / 2.0;
+        for (entity, dropped_item, position, velocity) in
+            (&entities, &dropped_items, &mut positions, &mut velocities).join()
+        {
+            if meshes.get(entity).is_none() {
+                // only blocks have a mesh for now, other items are invisible when dropped
+                if let Item::Block(block) = dropped_item.0.item {
+                    if let Some(mesh) = block_meshes.0.get(&block) {
+                        meshes.insert(entity, UvMesh(mesh.clone())).unwrap();
+                    }
+                }
+            }
+            // the block just below the item, so an item resting on a block stays there
+            let bottom =
+                ChunkMap::f_coords_to_coords(position.0 + Vector3::new(half.x, -0.001, half.z));
+            let (chunk_coords, block_coords) = ChunkMap::coords_to_chunk_coords_and_block(bottom);
+            let block = chunk_map
+                .get_chunk(chunk_coords)
+                .and_then(|chunk| chunks.get(chunk))
+                .map(|chunk| {
+                    (
+                        chunk.get_block(block_coords),
+                        chunk.get_facing(block_coords),
+                    )
+                });
+            // the top of the block below, lower than a whole block for slabs
+            let top = match block {
+                Some((block, facing)) if !block.is_replaceable() => Some(
+                    collision_boxes(block.shape(), block.orientation(), facing)
+                        .iter()
+                        .map(|(_, max)| max.y)
+                        .fold(0.0, f32::max)
+                        + bottom.y as f32,
+                ),
+                _ => None,
+            };
+            match (block, top) {
+                // wait in the air until the chunk below is loaded
+                (None, _) => velocity.0 = Vector3::new(0.0, 0.0, 0.0),
+                // rest on top of the block it fell into
+                (Some(_), Some(top)) if position.0.y <= top => {
+                    velocity.0 = Vector3::new(0.0, 0.0, 0.0);
+                    position.0.y = top;
+                }
+                (Some(_), _) => {
+                    velocity.0.y = (velocity.0.y - GRAVITY * dt).max(-TERMINAL_VELOCITY);
+                }
+            }
+        }
+
+        for (_, player_position, inventory) in (&players, &positions, &mut inventories).join() {
+            for (entity, dropped_item, position) in
+                (&entities, &mut dropped_items, &positions).join()
+            {
+                if (position.0 + half - player_position.0).magnitude() > PICKUP_DISTANCE {
+                    continue;
+                }
+                match inventory.add(dropped_item.0) {
+                    Some(left) => dropped_item.0 = left,
+                    None => entities.delete(entity).unwrap(),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::{Arc, Mutex};
+
+use crate::block_tick::BlockTicks;
+use crate::components::{LookedAt, Player};
+use crate::history::EditHistory;
+use crate::items::Inventory;
+use crate::resources::{GameMode, Hotbar};
+use crate::schematic::{Schematic, SCHEMATIC_DIRECTORY, SELECTION_SCHEMATIC};
+use crate::selection::{self, Selection, MAX_SELECTION_VOLUME};
+use crate::world_editor::WorldEditor;
+use crate::{chunk::Chunk, chunk_map::ChunkMap};
+use cgmath::Vector3;
+use simple_winit::input::{Input, MouseButton, VirtualKeyCode};
+use specs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
+use std::path::Path;
+
+/// B takes out the selection wand, with it left and right click mark the corners of a box,
+/// F fills it with the selected block, R replaces the looked at block in it with the selected one,
+/// H hollows it out, C copies it, P pastes the copy onto the looked at block and T turns the copy,
+/// K copies it and exports it as a schematic and L imports the schematic as the copy,
+/// only in creative mode and for selections of at most `MAX_SELECTION_VOLUME` blocks
+pub struct EditSelection;
+impl<'a> System<'a> for EditSelection {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Arc<Mutex<Input>>>,
+        ReadExpect<'a, GameMode>,
+        ReadExpect<'a, Hotbar>,
+        Write<'a, Selection>,
+        Write<'a, EditHistory>,
+        Read<'a, ChunkMap>,
+        WriteStorage<'a, Chunk>,
+        Write<'a, BlockTicks>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, LookedAt>,
+        ReadStorage<'a, Inventory>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            input,
+            game_mode,
+            hotbar,
+            mut selection,
+            mut history,
+            chunk_map,
+            mut chunks,
+            mut block_ticks,
+            players,
+            looked_at,
+            inventories,
+        ): Self::SystemData,
+    ) {
+        if *game_mode != GameMode::Creative {
+            selection.wand = false;
+            return;
+        }
+        let input = input.lock().unwrap();
+        if input.key_pressed(VirtualKeyCode::B) {
+            selection.wand = !selection.wand;
+        }
+        if !selection.wand {
+            return;
+        }
+        let looked_at = (&players, &looked_at).join().next().map(|(_, l)| l);
+        if let Some(looked_at) = looked_at {
+            if input.button_pressed(MouseButton::Left) {
+                selection.first = Some(looked_at.coords);
+            }
+            if input.button_pressed(MouseButton::Right) {
+                selection.second = Some(looked_at.coords);
+            }
+        }
+        let schematic = Path::new(SCHEMATIC_DIRECTORY).join(SELECTION_SCHEMATIC);
+        if input.key_pressed(VirtualKeyCode::L) {
+            match Schematic::load(&schematic).and_then(|s| s.to_clipboard()) {
+                Ok((clipboard, unknown)) => {
+                    if !unknown.is_empty() {
+                        eprintln!(
+                            "{} has unknown blocks {:?}, they are left empty",
+                            schematic.display(),
+                            unknown
+                        );
+                    }
+                    selection.clipboard = Some(clipboard);
+                }
+                Err(error) => eprintln!("could not import {}: {}", schematic.display(), error),
+            }
+        }
+        if input.key_pressed(VirtualKeyCode::T) {
+            selection.clipboard = selection.clipboard.as_ref().map(|c| c.rotated());
+        }
+
+        let bounds = match selection.bounds() {
+            Some((min, max)) => match selection::volume(min, max) {
+                Some(volume) if volume <= MAX_SELECTION_VOLUME => Some((min, max)),
+                _ => {
+                    let keys = [
+                        VirtualKeyCode::F,
+                        VirtualKeyCode::R,
+                        VirtualKeyCode::H,
+                        VirtualKeyCode::C,
+                        VirtualKeyCode::K,
+                    ];
+                    if keys.iter().any(|key| input.key_pressed(*key)) {
+                        eprintln!(
+                            "the selection is too big, it can have {} blocks at most",
+                            MAX_SELECTION_VOLUME
+                        );
+                    }
+                    None
+                }
+            },
+            None => None,
+        };
+        let held = (&players, &inventories)
+            .join()
+            .next()
+            .and_then(|(_, inventory)| inventory.held_block(hotbar.selected));
+        let mut editor = WorldEditor::batch(&chunk_map, &mut chunks, &mut block_ticks);
+        let changes = match bounds {
+            Some((min, max)) if input.key_pressed(VirtualKeyCode::F) => {
+                held.map(|block| selection::fill(min, max, block))
+            }
+            Some((min, max)) if input.key_pressed(VirtualKeyCode::R) => {
+                let from = looked_at.and_then(|l| editor.get_block(l.coords));
+                match (from, held) {
+                    (Some(from), Some(to)) => Some(selection::replace(min, max, from, to, |c| {
+                        editor.get_block(c)
+                    })),
+                    _ => None,
+                }
+            }
+            Some((min, max)) if input.key_pressed(VirtualKeyCode::H) => {
+                Some(selection::hollow(min, max))
+            }
+            Some((min, max)) if input.key_pressed(VirtualKeyCode::C) => {
+                selection.clipboard =
+                    Some(selection::copy(min, max, |c| editor.get_block_facing(c)));
+                None
+            }
+            Some((min, max)) if input.key_pressed(VirtualKeyCode::K) => {
+                let clipboard = selection::copy(min, max, |c| editor.get_block_facing(c));
+                if let Err(error) = Schematic::of(&clipboard).save(&schematic) {
+                    eprintln!("could not export {}: {}", schematic.display(), error);
+                }
+                selection.clipboard = Some(clipboard);
+                None
+            }
+            _ if input.key_pressed(VirtualKeyCode::P) => match (&selection.clipboard, looked_at) {
+                (Some(clipboard), Some(looked_at)) => {
+                    let dir: Vector3<i32> = looked_at.dir.into();
+                    Some(selection::paste(clipboard, looked_at.coords + dir))
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        // blocks in chunks that aren't loaded are left out
+        for (coords, block, facing) in changes.into_iter().flatten() {
+            editor.set_block_facing(coords, block, facing);
+        }
+        history.record(editor.finish());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::block_tick::BlockTicks;
+use crate::components::{FallingBlock, Position, UvMesh, Velocity};
+use crate::resources::{BlockMeshes, DeltaTime};
+use crate::state::{GRAVITY, TERMINAL_VELOCITY};
+use crate::world_editor::WorldEditor;
+use crate::{chunk::Chunk, chunk_map::ChunkMap};
+use cgmath::Vector3;
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
+
+pub struct FallingBlocks;
+impl<'a> System<'a> for FallingBlocks {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, DeltaTime>,
+        Read<'a, ChunkMap>,
+        WriteStorage<'a, Chunk>,
+        ReadStorage<'a, FallingBlock>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Velocity>,
+        WriteStorage<'a, UvMesh>,
+        ReadExpect<'a, BlockMeshes>,
+        Write<'a, BlockTicks>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            delta_time,
+            chunk_map,
+            mut chunks,
+            falling_blocks,
+            positions,
+            mut velocities,
+            mut meshes,
+            block_meshes,
+            mut block_ticks,
+        ): Self::SystemData,
+    ) {
+        let dt = delta_time.0;
+        let get_block = |chunks: &WriteStorage<'a, Chunk>, coords: Vector3<i32>| {
+            let (chunk_coords, block_coords) = ChunkMap::coords_to_chunk_coords_and_block(coords);
+            chunk_map
+                .get_chunk(chunk_coords)
+                .and_then(|chunk| chunks.get(chunk))
+                .map(|chunk| chunk.get_block(block_coords))
+        };
+        for (entity, falling_block, position, velocity) in
+            (&entities, &falling_blocks, &positions, &mut velocities).join()
+        {
+            if meshes.get(entity).is_none() {
+                if let Some(mesh) = block_meshes.0.get(&falling_block.0) {
+                    meshes.insert(entity, UvMesh(mesh.clone())).unwrap();
+                }
+            }
+            // the block the bottom of the falling block is currently in
+            let bottom = ChunkMap::f_coords_to_coords(position.0 + Vector3::new(0.5, 0.0, 0.5));
+            match get_block(&chunks, bottom) {
+                // wait in the air until the chunk below is loaded
+                None => velocity.0 = Vector3::new(0.0, 0.0, 0.0),
+                Some(block) if block.is_replaceable() => {
+                    velocity.0.y = (velocity.0.y - GRAVITY * dt).max(-TERMINAL_VELOCITY);
+                }
+                Some(_) => {
+                    let mut target = bottom + Vector3::unit_y();
+                    // the target may have been filled while falling, use the next free block above
+                    while let Some(block) = get_block(&chunks, target) {
+                        if block.is_replaceable() {
+                            break;
+                        }
+                        target += Vector3::unit_y();
+                    }
+                    if WorldEditor::new(&chunk_map, &mut chunks, &mut block_ticks)
+                        .set_block(target, falling_block.0)
+                    {
+                        entities.delete(entity).unwrap();
+                    } else {
+                        // the block above is not loaded, rest on top of the landing spot until it is
+                        velocity.0 = Vector3::new(0.0, 0.0, 0.0);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,18 @@
-use crate::components::{ChunkMesh, Player, Position};
-use crate::{blocks::Block, chunk_middle_ware::ChunkMeshMiddleWare};
-use crate::{
-    chunk::{Chunk, CHUNK_SIZE},
-    chunk_map::ChunkMap,
-};
-use cgmath::{MetricSpace, Vector3};
-use specs::{Entities, Join, LazyUpdate, Read, ReadStorage, System, Write, WriteExpect};
+use crate::chunk::Chunk;
+use crate::chunk_loading::{anchors, columns_to_load, ChunkTickets};
+use crate::components::{ChunkMesh, ChunkTicket, Position};
+use crate::erosion::HeightCache;
+use crate::resources::WorldSeed;
+use crate::storage::WorldStorage;
+use crate::structures::add_structures;
+use crate::world_gen::{generate_chunk, GeneratorSettings, WORLD_HEIGHT};
+use crate::{chunk_map::ChunkMap, chunk_middle_ware::ChunkMeshMiddleWare};
+use cgmath::Vector3;
+use specs::{Entities, LazyUpdate, Read, ReadExpect, ReadStorage, System, Write, WriteExpect};
+use std::collections::HashMap;
+
+/// how many chunk columns are generated at most in one run, so moving fast doesn't freeze the game
+const COLUMNS_PER_RUN: usize = 4;
 
 pub struct GenerateChunks;
 impl<'a> System<'a> for GenerateChunks {
@@ -14,50 +21,59 @@ impl<'a> System<'a> for GenerateChunks {
         Entities<'a>,
         Write<'a, ChunkMap>,
         Read<'a, LazyUpdate>,
-        ReadStorage<'a, Player>,
+        Read<'a, ChunkTickets>,
+        ReadStorage<'a, ChunkTicket>,
         ReadStorage<'a, Position>,
         WriteExpect<'a, ChunkMeshMiddleWare>,
+        ReadExpect<'a, WorldSeed>,
+        ReadExpect<'a, GeneratorSettings>,
+        Write<'a, HeightCache>,
+        ReadExpect<'a, WorldStorage>,
     );
 
     fn run(
         &mut self,
-        (entities, mut chunk_map, updater, player, positions, chunk_mesh_middleware): Self::SystemData,
+        (
+            entities,
+            mut chunk_map,
+            updater,
+            tickets,
+            entity_tickets,
+            positions,
+            chunk_mesh_middleware,
+            seed,
+            settings,
+            mut heights,
+            storage,
+        ): Self::SystemData,
     ) {
-        let create_chunk = |position, chunk, map: &mut ChunkMap| {
+        let mut generated: HashMap<Vector3<i32>, Chunk> = HashMap::new();
+        let anchors = anchors(&tickets, &entity_tickets, &positions);
+        let columns = columns_to_load(&anchors, |column| chunk_map.get_chunk(column).is_some());
+        for column in columns.into_iter().take(COLUMNS_PER_RUN) {
+            for y in 0..WORLD_HEIGHT {
+                let position = Vector3::new(column.x, y, column.z);
+                let saved = storage.load(position).unwrap_or_else(|error| {
+                    eprintln!("{}, generating it again", error);
+                    None
+                });
+                if let Some(record) = saved {
+                    generated.insert(position, record.into_chunk(seed.0));
+                    continue;
+                }
+                let mut chunk = generate_chunk(seed.0, &settings, &mut heights, position);
+                add_structures(seed.0, &settings, &mut heights, &mut chunk);
+                // it isn't saved yet
+                chunk.unsaved = true;
+                generated.insert(position, chunk);
+            }
+        }
+        for (position, chunk) in generated {
             let new_chunk = entities.create();
-            let mesh = chunk_mesh_middleware.load_chunk_mesh(Vec::new(), position);
+            let mesh = chunk_mesh_middleware.load_chunk_mesh(Default::default(), position);
             updater.insert(new_chunk, chunk);
             updater.insert(new_chunk, ChunkMesh(mesh));
-            map.set_chunk(position, new_chunk);
-        };
-
-        for (_, position) in (&player, &positions).join() {
-            let mut p = ChunkMap::f_coords_to_chunk_coords(position.0);
-            p.y = 0;
-
-            let range = 2;
-            for x in -range..range + 1 {
-                for z in -range..range + 1 {
-                    let x = x + p.x;
-                    let z = z + p.z;
-                    let chunk_position = Vector3::new(x, 0, z);
-                    if chunk_position.distance2(p) < range * range
-                        && chunk_map.get_chunk(chunk_position).is_none()
-                    {
-                        println!("generating chunk: {:?}", chunk_position);
-                        let mut chunk = Chunk::empty(chunk_position);
-                        *chunk.blocks = [[[Block::Grass; CHUNK_SIZE]; CHUNK_SIZE]; CHUNK_SIZE];
-                        chunk.regenerate_mesh = true;
-                        chunk.update_neighbours.west = Some(());
-                        chunk.update_neighbours.east = Some(());
-                        chunk.update_neighbours.north = Some(());
-                        chunk.update_neighbours.south = Some(());
-                        chunk.update_neighbours.up = Some(());
-                        chunk.update_neighbours.down = Some(());
-                        create_chunk(chunk_position, chunk, &mut chunk_map);
-                    }
-                }
-            }
+            chunk_map.set_chunk(position, new_chunk);
         }
     }
 }
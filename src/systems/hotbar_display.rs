@@ -0,0 +1,78 @@
+use crate::components::{FlatInstances, HotbarFrame, HotbarIcon, Player};
+use crate::flat_middleware::Rect;
+use crate::items::Inventory;
+use crate::resources::{Hotbar, HOTBAR_SIZE};
+use cgmath::Vector2;
+use finger_paint_wgpu::WgpuRenderer;
+use specs::{Join, ReadExpect, ReadStorage, System, WriteStorage};
+
+/// width of a slot in screen space
+const SLOT_SIZE: f32 = 0.04;
+const FRAME_WIDTH: f32 = 0.004;
+/// distance of the hotbar to the bottom of the screen
+const BOTTOM: f32 = 0.02;
+
+pub struct HotbarDisplay;
+impl<'a> System<'a> for HotbarDisplay {
+    type SystemData = (
+        ReadExpect<'a, Hotbar>,
+        ReadExpect<'a, WgpuRenderer>,
+        ReadStorage<'a, HotbarIcon>,
+        ReadStorage<'a, HotbarFrame>,
+        WriteStorage<'a, FlatInstances>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Inventory>,
+    );
+
+    fn run(
+        &mut self,
+        (hotbar, renderer, icons, frames, mut instances, players, inventories): Self::SystemData,
+    ) {
+        let aspect = renderer.aspect();
+        let pitch = SLOT_SIZE + FRAME_WIDTH * 2.0;
+        let left = 0.5 - pitch * HOTBAR_SIZE as f32 / 2.0;
+        let slot_height = SLOT_SIZE * aspect;
+        let frame_height = FRAME_WIDTH * aspect;
+        let rect = |x: f32, y: f32, w: f32, h: f32| Rect {
+            position: Vector2::new(x, y),
+            size: Vector2::new(w, h),
+        };
+
+        // the hotbar shows the first slots of the inventory
+        let inventory = (&players, &inventories).join().next().map(|(_, i)| i);
+        let held = |slot: usize| inventory.and_then(|i| i.held(slot)).map(|stack| stack.item);
+        for (icon, instances) in (&icons, &mut instances).join() {
+            instances.0.clear();
+            for i in 0..HOTBAR_SIZE {
+                if held(i) == Some(icon.0) {
+                    let x = left + i as f32 * pitch + FRAME_WIDTH;
+                    instances
+                        .0
+                        .push(rect(x, BOTTOM + frame_height, SLOT_SIZE, slot_height));
+                }
+            }
+        }
+        for (frame, instances) in (&frames, &mut instances).join() {
+            instances.0.clear();
+            for i in 0..HOTBAR_SIZE {
+                if (i == hotbar.selected) != frame.selected {
+                    continue;
+                }
+                // four bars around the slot, they must not overlap the icon
+                let x = left + i as f32 * pitch;
+                let top = BOTTOM + frame_height + slot_height;
+                instances.0.extend_from_slice(&[
+                    rect(x, BOTTOM, pitch, frame_height),
+                    rect(x, top, pitch, frame_height),
+                    rect(x, BOTTOM + frame_height, FRAME_WIDTH, slot_height),
+                    rect(
+                        x + FRAME_WIDTH + SLOT_SIZE,
+                        BOTTOM + frame_height,
+                        FRAME_WIDTH,
+                        slot_height,
+                    ),
+                ]);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex};
+
+use crate::components::{LookedAt, Player};
+use crate::items::{Inventory, Item, ItemStack};
+use crate::resources::{GameMode, Hotbar};
+use crate::{blocks::Block, chunk::Chunk, chunk_map::ChunkMap};
+use simple_winit::input::{Input, MouseButton, VirtualKeyCode};
+use specs::{Join, Read, ReadExpect, ReadStorage, System, WriteExpect, WriteStorage};
+
+const SLOT_KEYS: [VirtualKeyCode; 9] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+];
+
+pub struct HotbarInput;
+impl<'a> System<'a> for HotbarInput {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteExpect<'a, Hotbar>,
+        ReadExpect<'a, Arc<Mutex<Input>>>,
+        Read<'a, ChunkMap>,
+        ReadStorage<'a, Chunk>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, LookedAt>,
+        WriteStorage<'a, Inventory>,
+        ReadExpect<'a, GameMode>,
+    );
+
+    fn run(
+        &mut self,
+        (mut hotbar, input, chunk_map, chunks, players, looked_at, mut inventories, game_mode): Self::SystemData,
+    ) {
+        let input = input.lock().unwrap();
+        for (i, key) in SLOT_KEYS.iter().enumerate() {
+            if input.key_pressed(*key) {
+                hotbar.selected = i;
+            }
+        }
+        let scroll = input.scroll_diff();
+        if scroll > 0.0 {
+            hotbar.scroll(-1);
+        } else if scroll < 0.0 {
+            hotbar.scroll(1);
+        }
+
+        if input.button_pressed(MouseButton::Middle) {
+            for (_, looked_at, inventory) in (&players, &looked_at, &mut inventories).join() {
+                let (chunk_coords, block_coords) =
+                    ChunkMap::coords_to_chunk_coords_and_block(looked_at.coords);
+                if let Some(chunk) = chunk_map
+                    .get_chunk(chunk_coords)
+                    .and_then(|chunk| chunks.get(chunk))
+                {
+                    let block = chunk.get_block(block_coords);
+                    if block != Block::Empty {
+                        let item = Item::Block(block);
+                        match *game_mode {
+                            // in creative mode any block can be taken
+                            GameMode::Creative => {
+                                if let Some(slot) = inventory.slots.get_mut(hotbar.selected) {
+                                    *slot = Some(ItemStack::new(item, item.max_stack_size()));
+                                }
+                            }
+                            GameMode::Survival => {
+                                inventory.select_item(hotbar.selected, item);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,14 @@
+mod autosave;
 mod block_highlighting;
 mod break_blocks;
 mod chunk_mesh_generation;
+mod dropped_items;
+mod edit_selection;
+mod falling_blocks;
 mod first_person_controller;
 mod generate_chunks;
+mod hotbar_display;
+mod hotbar_input;
 mod looking_at;
 mod looking_at_marker;
 mod place_blocks;
@@ -12,17 +18,25 @@ mod render_models;
 mod render_uv_meshes;
 mod sun_system;
 mod third_person_camera_system;
+mod tick_blocks;
 mod transform_real_lights;
+mod undo_edits;
 mod update_camera;
 mod update_cameras;
 mod update_neighbouring_chunks;
 mod velocity_system;
 
+pub use autosave::Autosave;
 pub use block_highlighting::BlockHighlighting;
 pub use break_blocks::BreakBlocks;
 pub use chunk_mesh_generation::ChunkMeshGeneration;
+pub use dropped_items::DroppedItems;
+pub use edit_selection::EditSelection;
+pub use falling_blocks::FallingBlocks;
 pub use first_person_controller::FirstPersonController;
 pub use generate_chunks::GenerateChunks;
+pub use hotbar_display::HotbarDisplay;
+pub use hotbar_input::HotbarInput;
 pub use looking_at::LookingAtSystem;
 pub use looking_at_marker::LookingAtMarkerSystem;
 pub use place_blocks::PlaceBlocks;
@@ -32,7 +46,9 @@ pub use render_models::RenderModels;
 pub use render_uv_meshes::RenderUvMeshes;
 pub use sun_system::SunSystem;
 pub use third_person_camera_system::ThirdPersonCameraSystem;
+pub use tick_blocks::TickBlocks;
 pub use transform_real_lights::TransformRealLights;
+pub use undo_edits::UndoEdits;
 pub use update_camera::UpdateCamera;
 pub use update_cameras::UpdateCameras;
 pub use update_neighbouring_chunks::UpdateNeighbouringChunks;
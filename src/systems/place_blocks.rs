@@ -1,68 +1,120 @@
 use std::sync::{Arc, Mutex};
 
-use crate::{blocks::Block, components::LookedAt, resources::SoundPlayer};
-use crate::{chunk::Chunk, chunk_middle_ware::ChunkMeshMiddleWare, components::ChunkMesh};
+use crate::blocks::Orientation;
+use crate::components::FirstPersonController;
+use crate::dir::Dir;
+use crate::history::EditHistory;
+use crate::items::Inventory;
+use crate::resources::{GameMode, Hotbar, SoundPlayer};
+use crate::selection::Selection;
+use crate::world_editor::WorldEditor;
+use crate::{block_tick::BlockTicks, components::LookedAt};
+use crate::{
+    chunk::Chunk,
+    components::{BoundingBox, Position},
+};
 use crate::{chunk_map::ChunkMap, components::Player};
 use cgmath::Vector3;
 use simple_winit::input::Input;
-use specs::{Entities, Join, ReadExpect, ReadStorage, System, Write, WriteStorage};
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
 
 pub struct PlaceBlocks;
 
 impl<'a> System<'a> for PlaceBlocks {
     #[allow(clippy::type_complexity)]
     type SystemData = (
-        Write<'a, ChunkMap>,
+        Read<'a, ChunkMap>,
         WriteStorage<'a, Chunk>,
         ReadStorage<'a, Player>,
         ReadStorage<'a, LookedAt>,
         ReadExpect<'a, Arc<Mutex<Input>>>,
-        ReadExpect<'a, SoundPlayer>,
         Entities<'a>,
-        WriteStorage<'a, ChunkMesh>,
-        ReadExpect<'a, ChunkMeshMiddleWare>,
+        Write<'a, BlockTicks>,
+        ReadExpect<'a, Hotbar>,
+        ReadExpect<'a, GameMode>,
+        WriteStorage<'a, Inventory>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, BoundingBox>,
+        ReadStorage<'a, FirstPersonController>,
+        Write<'a, EditHistory>,
+        Read<'a, Selection>,
+        ReadExpect<'a, SoundPlayer>,
     );
 
     fn run(
         &mut self,
         (
-            mut chunk_map,
+            chunk_map,
             mut chunks,
             players,
             looked_at,
             input,
-            player,
             entities,
-            mut meshes,
-            chunk_middleware,
+            mut block_ticks,
+            hotbar,
+            game_mode,
+            mut inventories,
+            positions,
+            bounding_boxes,
+            controllers,
+            mut history,
+            selection,
+            player,
         ): Self::SystemData,
     ) {
+        if selection.wand {
+            return;
+        }
         let input = input.lock().unwrap();
-        for (_, looked_at) in (&players, &looked_at).join() {
+        for (entity, _, looked_at) in (&entities, &players, &looked_at).join() {
             if input.button_pressed(simple_winit::input::MouseButton::Right) {
+                let held = inventories
+                    .get(entity)
+                    .and_then(|inventory| inventory.held_block(hotbar.selected));
+                let block = match held {
+                    Some(block) => block,
+                    None => continue,
+                };
                 let dir: Vector3<i32> = looked_at.dir.into();
-                let (chunk_coords, block_coords) =
-                    ChunkMap::coords_to_chunk_coords_and_block(looked_at.coords + dir);
-                if let Some(chunk) = chunk_map.get_chunk(chunk_coords) {
-                    let chunk = chunks.get_mut(chunk).unwrap();
-                    chunk.set_block(block_coords, Block::Stone);
-                } else {
-                    let mut chunk = Chunk::empty(chunk_coords);
-                    chunk.set_block(block_coords, Block::Stone);
-                    let mesh =
-                        ChunkMesh(chunk_middleware.load_chunk_mesh(Vec::new(), chunk_coords));
-
-                    let chunk = entities
-                        .build_entity()
-                        .with(chunk, &mut chunks)
-                        .with(mesh, &mut meshes)
-                        .build();
-                    let sand = ton::PlayableSfxr::load_from_json(
-                        &std::fs::read_to_string("./sand.json").unwrap(),
-                    )
-                    .unwrap();
-                    player.play(sand).detach();
-                    chunk_map.set_chunk(chunk_coords, chunk);
+                let coords = looked_at.coords + dir;
+                let mut editor = WorldEditor::new(&chunk_map, &mut chunks, &mut block_ticks);
+                // blocks can only be placed into generated chunks
+                match editor.get_block(coords) {
+                    Some(old) if old.is_replaceable() => {}
+                    _ => continue,
+                }
+                if (&positions, &bounding_boxes)
+                    .join()
+                    .any(|(position, bounding_box)| {
+                        bounding_box.intersects_block(position.0, coords)
+                    })
+                {
+                    continue;
+                }
+                // in survival mode every placed block is taken from the inventory
+                if *game_mode == GameMode::Survival {
+                    if let Some(inventory) = inventories.get_mut(entity) {
+                        inventory.take_held(hotbar.selected);
+                    }
+                }
+                let facing = match block.orientation() {
+                    Orientation::Fixed => Dir::Up,
+                    Orientation::Axis => looked_at.dir,
+                    // the front faces the player
+                    Orientation::Horizontal => controllers
+                        .get(entity)
+                        .map_or(Dir::North, |c| Dir::from_yaw(c.yaw).opposite()),
+                };
+                if let Some(sound) = block.sound() {
+                    let sound =
+                        ton::PlayableSfxr::load_from_json(&std::fs::read_to_string(sound).unwrap())
+                            .unwrap();
+                    player.play(sound).detach();
+                }
+                editor.set_block_facing(coords, block, facing);
+                let edits = editor.finish();
+                if *game_mode == GameMode::Creative {
+                    history.record(edits);
                 }
             }
         }
@@ -1,45 +1,46 @@
-use crate::components::{Player, Position};
+use crate::chunk_loading::{anchors, keeps_loaded, keeps_loaded_near, ChunkTickets};
+use crate::components::{ChunkTicket, Position};
+use crate::erosion::HeightCache;
+use crate::storage::{ChunkRecord, WorldStorage};
 use crate::{chunk::Chunk, chunk_map::ChunkMap};
-use cgmath::MetricSpace;
-use specs::{Entities, Join, ReadStorage, System, Write, WriteStorage};
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
 
 pub struct RemoveChunks;
 impl<'a> System<'a> for RemoveChunks {
     #[allow(clippy::type_complexity)]
     type SystemData = (
-        ReadStorage<'a, Player>,
+        Read<'a, ChunkTickets>,
+        ReadStorage<'a, ChunkTicket>,
         ReadStorage<'a, Position>,
         WriteStorage<'a, Chunk>,
         Entities<'a>,
         Write<'a, ChunkMap>,
+        ReadExpect<'a, WorldStorage>,
+        Write<'a, HeightCache>,
     );
 
-    fn run(&mut self, (player, positions, chunks, entities, mut chunk_map): Self::SystemData) {
-        let range = 8;
-        let mut player_positions = Vec::new();
-
-        for (_, position) in (&player, &positions).join() {
-            let mut p = ChunkMap::f_coords_to_chunk_coords(position.0);
-            p.y = 0;
-            player_positions.push(p);
-        }
+    fn run(
+        &mut self,
+        (tickets, entity_tickets, positions, chunks, entities, mut chunk_map, storage, mut heights): Self::SystemData,
+    ) {
+        let anchors = anchors(&tickets, &entity_tickets, &positions);
         let mut chunks_to_remove = Vec::new();
+        let mut unsaved = Vec::new();
         for (entity, chunk) in (&entities, &chunks).join() {
-            let mut remove = true;
-            let position = chunk.position;
-            for player_position in &player_positions {
-                if position.distance2(*player_position) < range * range {
-                    remove = false;
-                }
-            }
-            if remove {
+            if !keeps_loaded(&anchors, chunk.position) {
                 chunks_to_remove.push(entity);
+                if chunk.unsaved {
+                    unsaved.push(ChunkRecord::of(chunk));
+                }
             }
         }
+        storage.save(unsaved);
+        storage.forget_regions(|column, reach| keeps_loaded_near(&anchors, column, reach));
         for chunk in chunks_to_remove {
-            println!("deleting chunk");
             chunk_map.remove_chunk(chunks.get(chunk).unwrap().position);
             entities.delete(chunk).unwrap();
         }
+        // the eroded regions are only needed to generate chunks
+        heights.forget_regions(|column, reach| keeps_loaded_near(&anchors, column, reach));
     }
 }
@@ -1,4 +1,4 @@
-use crate::components::{FlatMesh, Position, Scale};
+use crate::components::{FlatInstances, FlatMesh, Position, Scale};
 use crate::flat_middleware::Rect;
 use cgmath::Vector2;
 use specs::{Entities, Join, ReadStorage, System, WriteStorage};
@@ -11,9 +11,10 @@ impl<'a> System<'a> for RenderFlatMeshes {
         WriteStorage<'a, FlatMesh>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, Scale>,
+        ReadStorage<'a, FlatInstances>,
     );
 
-    fn run(&mut self, (entities, mut meshes, position, scale): Self::SystemData) {
+    fn run(&mut self, (entities, mut meshes, position, scale, instances): Self::SystemData) {
         for (_, mesh) in (&entities, &mut meshes).join() {
             mesh.0.instances.clear();
         }
@@ -27,6 +28,9 @@ impl<'a> System<'a> for RenderFlatMeshes {
                 },
             })
         }
+        for (model, instances) in (&mut meshes, &instances).join() {
+            model.0.instances.extend(instances.0.iter().copied());
+        }
         for (_, mesh) in (&entities, &mut meshes).join() {
             mesh.0.update();
         }
@@ -0,0 +1,73 @@
+use cgmath::Vector3;
+use rand::Rng;
+use specs::{Entities, Join, LazyUpdate, Read, ReadExpect, System, Write, WriteStorage};
+
+use crate::block_tick::{
+    schedule_changed_blocks, BlockBehaviours, BlockTicks, TickContext, RANDOM_TICK_SPEED,
+};
+use crate::chunk::{Chunk, CHUNK_SIZE};
+use crate::{chunk_map::ChunkMap, resources::DeltaTime};
+
+pub struct TickBlocks;
+impl<'a> System<'a> for TickBlocks {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Read<'a, DeltaTime>,
+        Read<'a, ChunkMap>,
+        WriteStorage<'a, Chunk>,
+        Write<'a, BlockTicks>,
+        ReadExpect<'a, BlockBehaviours>,
+        Entities<'a>,
+        Read<'a, LazyUpdate>,
+    );
+
+    fn run(
+        &mut self,
+        (delta_time, chunk_map, mut chunks, mut ticks, behaviours, entities, lazy): Self::SystemData,
+    ) {
+        let game_ticks = ticks.advance(delta_time.0);
+        let mut ctx = TickContext {
+            chunk_map: &chunk_map,
+            chunks: &mut chunks,
+            ticks: &mut ticks,
+            entities: &entities,
+            lazy: &lazy,
+        };
+        let mut rng = rand::thread_rng();
+        for _ in 0..game_ticks {
+            schedule_changed_blocks(&mut ctx, &behaviours);
+            for coords in ctx.ticks.next_tick() {
+                if let Some(behaviour) = ctx.get_block(coords).and_then(|b| behaviours.get(b)) {
+                    behaviour.scheduled_tick(&mut ctx, coords);
+                }
+            }
+
+            let mut random_ticks = Vec::new();
+            for chunk in (&*ctx.chunks).join() {
+                for _ in 0..RANDOM_TICK_SPEED {
+                    let block_coords = Vector3::new(
+                        rng.gen_range(0, CHUNK_SIZE as u16),
+                        rng.gen_range(0, CHUNK_SIZE as u16),
+                        rng.gen_range(0, CHUNK_SIZE as u16),
+                    );
+                    let block = chunk.get_block(block_coords);
+                    if behaviours.get(block).is_some() {
+                        random_ticks.push(
+                            chunk.position * CHUNK_SIZE as i32
+                                + Vector3::new(
+                                    block_coords.x as i32,
+                                    block_coords.y as i32,
+                                    block_coords.z as i32,
+                                ),
+                        );
+                    }
+                }
+            }
+            for coords in random_ticks {
+                if let Some(behaviour) = ctx.get_block(coords).and_then(|b| behaviours.get(b)) {
+                    behaviour.random_tick(&mut ctx, coords);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::{Arc, Mutex};
+
+use crate::block_tick::BlockTicks;
+use crate::history::EditHistory;
+use crate::resources::GameMode;
+use crate::storage::WorldStorage;
+use crate::world_editor::WorldEditor;
+use crate::{chunk::Chunk, chunk_map::ChunkMap};
+use simple_winit::input::{Input, VirtualKeyCode};
+use specs::{Read, ReadExpect, System, Write, WriteStorage};
+
+/// undoes the last block edit with Z and redoes it with X, only in creative mode so nothing is gained by it
+pub struct UndoEdits;
+impl<'a> System<'a> for UndoEdits {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Arc<Mutex<Input>>>,
+        ReadExpect<'a, GameMode>,
+        Write<'a, EditHistory>,
+        Read<'a, ChunkMap>,
+        WriteStorage<'a, Chunk>,
+        Write<'a, BlockTicks>,
+        ReadExpect<'a, WorldStorage>,
+    );
+
+    fn run(
+        &mut self,
+        (input, game_mode, mut history, chunk_map, mut chunks, mut block_ticks, storage): Self::SystemData,
+    ) {
+        if *game_mode != GameMode::Creative {
+            return;
+        }
+        let input = input.lock().unwrap();
+        let changes = if input.key_pressed(VirtualKeyCode::Z) {
+            history.undo()
+        } else if input.key_pressed(VirtualKeyCode::X) {
+            history.redo()
+        } else {
+            None
+        };
+        let mut editor = WorldEditor::batch(&chunk_map, &mut chunks, &mut block_ticks);
+        let mut unloaded = Vec::new();
+        for (coords, block, facing) in changes.into_iter().flatten() {
+            if !editor.set_block_facing(coords, block, facing) {
+                unloaded.push((coords, block, facing));
+            }
+        }
+        // the chunks were unloaded since, so they are changed where they are saved
+        storage.change_blocks(unloaded);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::block_tick::BlockTicks;
+use crate::blocks::Block;
+use crate::chunk::Chunk;
+use crate::chunk_map::ChunkMap;
+use crate::dir::Dir;
+use crate::history::BlockEdit;
+use crate::neighbours::Neighbours;
+use cgmath::Vector3;
+use specs::{Entity, WriteStorage};
+use std::collections::{HashMap, HashSet};
+
+/// what a batch changed, the chunks are flagged and the block ticks notified once it's finished
+#[derive(Default)]
+struct Batch {
+    /// the changed chunks and the sides their neighbours have to be updated on
+    chunks: HashMap<Entity, Neighbours<()>>,
+    blocks: HashSet<Vector3<i32>>,
+}
+
+/// reads and changes the blocks of the loaded chunks in world coordinates,
+/// every change notifies the block ticks and is remembered for the edit history
+pub struct WorldEditor<'a, 'b> {
+    chunk_map: &'a ChunkMap,
+    chunks: &'a mut WriteStorage<'b, Chunk>,
+    ticks: &'a mut BlockTicks,
+    batch: Option<Batch>,
+    edits: Vec<BlockEdit>,
+}
+
+impl<'a, 'b> WorldEditor<'a, 'b> {
+    /// an editor whose changes show up right away
+    pub fn new(
+        chunk_map: &'a ChunkMap,
+        chunks: &'a mut WriteStorage<'b, Chunk>,
+        ticks: &'a mut BlockTicks,
+    ) -> Self {
+        Self {
+            chunk_map,
+            chunks,
+            ticks,
+            batch: None,
+            edits: Vec::new(),
+        }
+    }
+    /// an editor for many changes at once, every chunk is flagged and every block is notified once
+    /// when it's finished, no matter how often they were changed
+    pub fn batch(
+        chunk_map: &'a ChunkMap,
+        chunks: &'a mut WriteStorage<'b, Chunk>,
+        ticks: &'a mut BlockTicks,
+    ) -> Self {
+        let mut editor = Self::new(chunk_map, chunks, ticks);
+        editor.batch = Some(Batch::default());
+        editor
+    }
+    fn chunk(&self, coords: Vector3<i32>) -> Option<(Entity, Vector3<u16>)> {
+        let (chunk_coords, block_coords) = ChunkMap::coords_to_chunk_coords_and_block(coords);
+        let entity = self.chunk_map.get_chunk(chunk_coords)?;
+        Some((entity, block_coords))
+    }
+    /// returns `None` if the chunk containing `coords` is not loaded
+    pub fn get_block(&self, coords: Vector3<i32>) -> Option<Block> {
+        self.get_block_facing(coords).map(|(block, _)| block)
+    }
+    pub fn get_block_facing(&self, coords: Vector3<i32>) -> Option<(Block, Dir)> {
+        let (entity, block_coords) = self.chunk(coords)?;
+        let chunk = self.chunks.get(entity)?;
+        Some((
+            chunk.get_block(block_coords),
+            chunk.get_facing(block_coords),
+        ))
+    }
+    /// returns false if the chunk containing `coords` is not loaded
+    pub fn set_block(&mut self, coords: Vector3<i32>, block: Block) -> bool {
+        self.set_block_facing(coords, block, Dir::Up)
+    }
+    pub fn set_block_facing(&mut self, coords: Vector3<i32>, block: Block, facing: Dir) -> bool {
+        let (entity, block_coords) = match self.chunk(coords) {
+            Some(chunk) => chunk,
+            None => return false,
+        };
+        let chunk = match self.chunks.get_mut(entity) {
+            Some(chunk) => chunk,
+            None => return false,
+        };
+        let old = (
+            chunk.get_block(block_coords),
+            chunk.get_facing(block_coords),
+        );
+        if !chunk.write_block(block_coords, block, facing) {
+            return true;
+        }
+        let sides = Chunk::sides_touched(block_coords);
+        match &mut self.batch {
+            Some(batch) => {
+                let changed = batch.chunks.entry(entity).or_insert_with(Neighbours::new);
+                for (dir, _) in sides.iter() {
+                    changed.set(dir, ());
+                }
+                batch.blocks.insert(coords);
+            }
+            None => {
+                chunk.mark_changed(&sides);
+                self.ticks.block_changed(coords);
+            }
+        }
+        self.edits.push(BlockEdit {
+            coords,
+            old,
+            new: (block, facing),
+        });
+        true
+    }
+    /// flags the chunks and notifies the block ticks of what the batch changed so far
+    fn flush(&mut self) {
+        let batch = match &mut self.batch {
+            Some(batch) => std::mem::take(batch),
+            None => return,
+        };
+        for (entity, sides) in batch.chunks {
+            if let Some(chunk) = self.chunks.get_mut(entity) {
+                chunk.mark_changed(&sides);
+            }
+        }
+        for coords in batch.blocks {
+            self.ticks.block_changed(coords);
+        }
+    }
+    /// the changes in the order they were made, to be recorded in the edit history
+    pub fn finish(mut self) -> Vec<BlockEdit> {
+        self.flush();
+        std::mem::take(&mut self.edits)
+    }
+}
+
+impl Drop for WorldEditor<'_, '_> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[test]
+fn batches_flag_every_chunk_once_they_are_finished() {
+    use specs::{Builder, Join, World, WorldExt};
+    let mut world = World::new();
+    world.register::<Chunk>();
+    let mut chunk_map = ChunkMap::new();
+    for x in 0..2 {
+        let position = Vector3::new(x, 0, 0);
+        let entity = world.create_entity().with(Chunk::empty(position)).build();
+        chunk_map.set_chunk(position, entity);
+    }
+    let mut ticks = BlockTicks::new();
+    let mut chunks = world.write_storage::<Chunk>();
+    let flagged = |chunks: &WriteStorage<'_, Chunk>| {
+        let mut flagged: Vec<_> = chunks
+            .join()
+            .map(|chunk| (chunk.position.x, chunk.regenerate_mesh, chunk.unsaved))
+            .collect();
+        flagged.sort_unstable();
+        flagged
+    };
+
+    let mut editor = WorldEditor::batch(&chunk_map, &mut chunks, &mut ticks);
+    for x in 10..16 {
+        assert!(editor.set_block(Vector3::new(x, 3, 4), Block::Stone));
+        assert!(editor.set_block(Vector3::new(x, 3, 4), Block::Sand));
+    }
+    assert!(!editor.set_block(Vector3::new(40, 3, 4), Block::Stone));
+    assert_eq!(editor.get_block(Vector3::new(12, 3, 4)), Some(Block::Sand));
+    drop(editor);
+    let edits = {
+        let mut editor = WorldEditor::batch(&chunk_map, &mut chunks, &mut ticks);
+        editor.set_block(Vector3::new(20, 3, 4), Block::Log);
+        // nothing is flagged before the batch is finished
+        assert_eq!(
+            flagged(editor.chunks),
+            vec![(0, true, true), (1, false, false)]
+        );
+        editor.finish()
+    };
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].old, (Block::Empty, Dir::Up));
+
+    assert_eq!(flagged(&chunks), vec![(0, true, true), (1, true, true)]);
+    let chunk = (&chunks)
+        .join()
+        .find(|chunk| chunk.position.x == 0)
+        .unwrap();
+    let sides: Vec<_> = chunk.update_neighbours.iter().map(|(dir, _)| dir).collect();
+    assert_eq!(sides, vec![Dir::East]);
+
+    // setting a block to what it is already changes nothing
+    for chunk in (&mut chunks).join() {
+        chunk.regenerate_mesh = false;
+    }
+    let mut editor = WorldEditor::new(&chunk_map, &mut chunks, &mut ticks);
+    assert!(editor.set_block(Vector3::new(20, 3, 4), Block::Log));
+    assert!(editor.finish().is_empty());
+    assert_eq!(flagged(&chunks), vec![(0, false, true), (1, false, true)]);
+}
@@ -0,0 +1,541 @@
+use crate::biomes::{biome_at, Biome};
+use crate::blocks::Block;
+use crate::chunk::{Chunk, CHUNK_SIZE};
+use crate::erosion::HeightCache;
+use crate::noise::{fractal_2d, fractal_3d, hash, random_2d};
+use cgmath::Vector3;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// how many chunks the world goes up from y 0
+pub const WORLD_HEIGHT: i32 = 3;
+/// how many blocks of filler are between the surface and the stone
+const FILLER_DEPTH: i32 = 3;
+/// how many columns in every direction the height profiles of the biomes are averaged over
+pub const HEIGHT_BLEND_RADIUS: i32 = 4;
+/// how big the hills are in blocks
+const HILL_SCALE: f32 = 48.0;
+
+/// how long the bends of the cave tunnels are in blocks
+const CAVE_SCALE: f32 = 40.0;
+/// how far the two noises may be from their middle for a tunnel, squared
+const CAVE_RADIUS: f32 = 0.0016;
+/// how long the bends of the ravines are in blocks
+const RAVINE_SCALE: f32 = 96.0;
+/// how far the ravine noise may be from its middle
+const RAVINE_WIDTH: f32 = 0.012;
+/// how many blocks deep the middle of a ravine goes
+const RAVINE_DEPTH: f32 = 22.0;
+/// the ground under water stays this thick over caves, so the water doesn't pour into them
+const SEA_FLOOR: i32 = 4;
+
+const HILL_SEED: u64 = 0x4111;
+const DECORATION_SEED: u64 = 0xDEC0;
+const CAVE_SEEDS: [u64; 2] = [0xCA7E_0000, 0xCA7E_1000];
+const RAVINE_SEED: u64 = 0x7A71;
+const RAVINE_AREA_SEED: u64 = 0x7A72;
+const ORE_SEED: u64 = 0x0FE0_0000;
+
+/// the parts of generation that can be chosen for a world, next to its seed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct GeneratorSettings {
+    /// all the open space below this height is filled with water
+    pub sea_level: i32,
+    /// how many blocks above the sea level the ground is still sand
+    pub beach_height: i32,
+    /// whether rain wears valleys and river beds into the ground before the blocks are placed
+    pub erosion: bool,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            sea_level: 12,
+            beach_height: 1,
+            erosion: true,
+        }
+    }
+}
+
+/// veins of an ore, placed in the stone between `min_y` and `max_y`
+struct OreVeins {
+    block: Block,
+    min_y: i32,
+    max_y: i32,
+    /// how many veins every chunk in the band gets
+    count: u32,
+    /// how many steps a vein wanders, every step can turn a stone block into ore
+    size: u32,
+}
+
+const ORES: [OreVeins; 3] = [
+    OreVeins {
+        block: Block::CoalOre,
+        min_y: 4,
+        max_y: 40,
+        count: 10,
+        size: 10,
+    },
+    OreVeins {
+        block: Block::IronOre,
+        min_y: 1,
+        max_y: 24,
+        count: 6,
+        size: 7,
+    },
+    OreVeins {
+        block: Block::GoldOre,
+        min_y: 1,
+        max_y: 12,
+        count: 3,
+        size: 6,
+    },
+];
+
+/// how many blocks of ground the column at `x` `z` has before erosion
+pub fn surface_height(seed: u64, x: i32, z: i32) -> i32 {
+    height_to_blocks(blended_height(seed, x, z, |x, z| {
+        biome_at(seed, x, z).height()
+    }))
+}
+
+/// the height of the ground from the height profiles of the biomes around the column,
+/// they are averaged so there are no cliffs at their borders,
+/// `profile` gives the height profile of the biome of a column
+pub fn blended_height(seed: u64, x: i32, z: i32, profile: impl Fn(i32, i32) -> (f32, f32)) -> f32 {
+    let mut base = 0.0;
+    let mut amplitude = 0.0;
+    let mut count = 0.0;
+    for dx in -HEIGHT_BLEND_RADIUS..HEIGHT_BLEND_RADIUS + 1 {
+        for dz in -HEIGHT_BLEND_RADIUS..HEIGHT_BLEND_RADIUS + 1 {
+            let (b, a) = profile(x + dx, z + dz);
+            base += b;
+            amplitude += a;
+            count += 1.0;
+        }
+    }
+    let hills = fractal_2d(seed ^ HILL_SEED, x as f32, z as f32, HILL_SCALE, 4) * 2.0 - 1.0;
+    (base + amplitude * hills) / count
+}
+
+/// the number of blocks of ground for a height, there is always at least one
+pub fn height_to_blocks(height: f32) -> i32 {
+    let top = WORLD_HEIGHT * CHUNK_SIZE as i32 - 1;
+    (height.round() as i32).max(1).min(top)
+}
+
+/// the lowest block a ravine carves out of the column, if one goes through it
+fn ravine_floor(seed: u64, x: i32, z: i32, height: i32) -> Option<i32> {
+    let (fx, fz) = (x as f32, z as f32);
+    // ravines are only in some areas, or they would be everywhere
+    if fractal_2d(seed ^ RAVINE_AREA_SEED, fx, fz, RAVINE_SCALE * 2.0, 1) < 0.6 {
+        return None;
+    }
+    let distance = (fractal_2d(seed ^ RAVINE_SEED, fx, fz, RAVINE_SCALE, 2) - 0.5).abs();
+    if distance >= RAVINE_WIDTH {
+        return None;
+    }
+    // deepest in the middle
+    let depth = RAVINE_DEPTH * (1.0 - distance / RAVINE_WIDTH).sqrt();
+    Some((height - depth as i32).max(2))
+}
+
+/// whether a cave tunnel goes through the block at this world position,
+/// the tunnels are where two noises are both close to their middle, so they wind through every chunk alike
+fn is_cave(seed: u64, x: i32, y: i32, z: i32) -> bool {
+    // squashed, so the tunnels rather go sideways than up
+    let position = [x as f32, y as f32 * 1.5, z as f32];
+    let a = fractal_3d(seed ^ CAVE_SEEDS[0], position, CAVE_SCALE, 2) - 0.5;
+    let b = fractal_3d(seed ^ CAVE_SEEDS[1], position, CAVE_SCALE, 2) - 0.5;
+    a * a + b * b < CAVE_RADIUS
+}
+
+/// the blocks of the veins of `ore` that start in the chunk sized cell of the world at `cell`, in world coordinates,
+/// the random numbers only depend on the seed and the cell, so a vein is the same in every chunk it reaches into
+fn ore_veins(seed: u64, cell: Vector3<i32>, index: usize, ore: &OreVeins) -> Vec<Vector3<i32>> {
+    let size = CHUNK_SIZE as i32;
+    let bottom = cell.y * size;
+    let min_y = ore.min_y.max(bottom);
+    let max_y = ore.max_y.min(bottom + size);
+    let mut blocks = Vec::new();
+    if min_y >= max_y {
+        return blocks;
+    }
+    let ore_seed = ORE_SEED.wrapping_add(index as u64);
+    let mut rng = StdRng::seed_from_u64(hash(seed ^ ore_seed, cell.x, cell.y, cell.z));
+    for _ in 0..ore.count {
+        let mut block = Vector3::new(
+            cell.x * size + rng.gen_range(0, size),
+            rng.gen_range(min_y, max_y),
+            cell.z * size + rng.gen_range(0, size),
+        );
+        for _ in 0..ore.size {
+            // veins wander, but stay in their band
+            if block.y >= ore.min_y && block.y < ore.max_y {
+                blocks.push(block);
+            }
+            block += Vector3::new(
+                rng.gen_range(-1, 2),
+                rng.gen_range(-1, 2),
+                rng.gen_range(-1, 2),
+            );
+        }
+    }
+    blocks
+}
+
+/// the parts of the ore veins that reach into the chunk, veins are shorter than a chunk,
+/// so only the ones starting in the chunk and the chunks around it can
+fn place_ores(seed: u64, chunk: &mut Chunk) {
+    let size = CHUNK_SIZE as i32;
+    let origin = chunk.position * size;
+    let inside = |c: i32| c >= 0 && c < size;
+    for dx in -1..2 {
+        for dy in -1..2 {
+            for dz in -1..2 {
+                let cell = chunk.position + Vector3::new(dx, dy, dz);
+                for (i, ore) in ORES.iter().enumerate() {
+                    for block in ore_veins(seed, cell, i, ore) {
+                        let local = block - origin;
+                        if inside(local.x) && inside(local.y) && inside(local.z) {
+                            let (x, y, z) = (local.x as usize, local.y as usize, local.z as usize);
+                            if chunk.blocks[x][y][z] == Block::Stone {
+                                chunk.blocks[x][y][z] = ore.block;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// what is known about a column of the world before its blocks are placed
+struct Column {
+    x: i32,
+    z: i32,
+    biome: Biome,
+    /// how many blocks of ground the column has
+    height: i32,
+    /// the lowest block a ravine carves out of the column, if one goes through it
+    ravine: Option<i32>,
+    settings: GeneratorSettings,
+}
+
+impl Column {
+    fn new(
+        seed: u64,
+        settings: &GeneratorSettings,
+        heights: &mut HeightCache,
+        x: i32,
+        z: i32,
+    ) -> Self {
+        let height = heights.height(seed, settings, x, z);
+        Self {
+            x,
+            z,
+            biome: biome_at(seed, x, z),
+            height,
+            ravine: ravine_floor(seed, x, z, height),
+            settings: *settings,
+        }
+    }
+    /// whether the sea reaches the ground of this column
+    fn is_wet(&self) -> bool {
+        self.height <= self.settings.sea_level
+    }
+    /// whether the ground is sand because the sea is close, mountains stay stone
+    fn is_beach(&self) -> bool {
+        self.biome != Biome::Mountains
+            && self.height <= self.settings.sea_level + self.settings.beach_height
+    }
+    fn surface(&self) -> Block {
+        if self.is_beach() {
+            Block::Sand
+        } else {
+            self.biome.surface()
+        }
+    }
+    fn filler(&self) -> Block {
+        if self.is_beach() {
+            Block::Sand
+        } else {
+            self.biome.filler()
+        }
+    }
+    /// whether caves and ravines carve out the block at `y`
+    fn is_carved(&self, seed: u64, y: i32) -> bool {
+        if y < 1 || y >= self.height {
+            return false;
+        }
+        if self.is_wet() {
+            return y < self.height - SEA_FLOOR && is_cave(seed, self.x, y, self.z);
+        }
+        matches!(self.ravine, Some(floor) if y >= floor) || is_cave(seed, self.x, y, self.z)
+    }
+    /// the block at the height `y`, before caves and ores
+    fn block(&self, y: i32, decoration: Option<Block>) -> Block {
+        if y < self.height - FILLER_DEPTH {
+            Block::Stone
+        } else if y < self.height - 1 {
+            self.filler()
+        } else if y == self.height - 1 {
+            self.surface()
+        } else if y < self.settings.sea_level {
+            Block::Water
+        } else if y == self.height {
+            decoration.unwrap_or(Block::Empty)
+        } else {
+            Block::Empty
+        }
+    }
+}
+
+/// the biome and the height of the ground of the column at `x` `z`,
+/// or nothing when the ground is under water or a cave or a ravine took away its top block
+pub fn intact_surface(
+    seed: u64,
+    settings: &GeneratorSettings,
+    heights: &mut HeightCache,
+    x: i32,
+    z: i32,
+) -> Option<(Biome, i32)> {
+    let column = Column::new(seed, settings, heights, x, z);
+    if column.height < settings.sea_level || column.is_carved(seed, column.height - 1) {
+        None
+    } else {
+        Some((column.biome, column.height))
+    }
+}
+
+/// generates the chunk at `position` in chunk coordinates,
+/// the same seed and settings always give the same chunk
+pub fn generate_chunk(
+    seed: u64,
+    settings: &GeneratorSettings,
+    heights: &mut HeightCache,
+    position: Vector3<i32>,
+) -> Chunk {
+    let size = CHUNK_SIZE as i32;
+    let mut chunk = Chunk::empty(position);
+    chunk.generate_climate(seed);
+    for x in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            let world_x = position.x * size + x as i32;
+            let world_z = position.z * size + z as i32;
+            let column = Column::new(seed, settings, heights, world_x, world_z);
+            // plants only grow on grass that a cave didn't open up
+            let decoration = column.biome.decoration().and_then(|(block, chance)| {
+                if column.surface() == Block::Grass
+                    && column.height >= settings.sea_level
+                    && random_2d(seed ^ DECORATION_SEED, world_x, world_z) < chance
+                    && !column.is_carved(seed, column.height - 1)
+                {
+                    Some(block)
+                } else {
+                    None
+                }
+            });
+            for y in 0..CHUNK_SIZE {
+                let world_y = position.y * size + y as i32;
+                chunk.blocks[x][y][z] = if column.is_carved(seed, world_y) {
+                    // open space below the sea level is water, inside the ground too
+                    if world_y < settings.sea_level {
+                        Block::Water
+                    } else {
+                        Block::Empty
+                    }
+                } else {
+                    column.block(world_y, decoration)
+                };
+            }
+        }
+    }
+    place_ores(seed, &mut chunk);
+    chunk.regenerate_mesh = true;
+    chunk.update_neighbours.west = Some(());
+    chunk.update_neighbours.east = Some(());
+    chunk.update_neighbours.north = Some(());
+    chunk.update_neighbours.south = Some(());
+    chunk.update_neighbours.up = Some(());
+    chunk.update_neighbours.down = Some(());
+    chunk
+}
+
+#[test]
+fn generation_is_deterministic_across_chunk_borders() {
+    let seed = 1234;
+    let settings = GeneratorSettings::default();
+    let mut heights = HeightCache::default();
+    let size = CHUNK_SIZE as i32;
+    for &(chunk_x, chunk_z) in &[(-1, 0), (0, 0), (0, -1)] {
+        let chunks: Vec<Chunk> = (0..WORLD_HEIGHT)
+            .map(|y| {
+                generate_chunk(
+                    seed,
+                    &settings,
+                    &mut heights,
+                    Vector3::new(chunk_x, y, chunk_z),
+                )
+            })
+            .collect();
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let world_x = chunk_x * size + x as i32;
+                let world_z = chunk_z * size + z as i32;
+                let column = Column::new(seed, &settings, &mut heights, world_x, world_z);
+                assert_eq!(column.biome, biome_at(seed, world_x, world_z));
+                let top = column.height - 1;
+                let block = chunks[(top / size) as usize].blocks[x][(top % size) as usize][z];
+                // unless a cave comes out here or an ore vein is in the bare stone of a mountain
+                let ore = ORES.iter().any(|ore| ore.block == block);
+                if !ore && !column.is_carved(seed, top) {
+                    assert_eq!(block, column.surface());
+                }
+            }
+        }
+        let again = generate_chunk(
+            seed,
+            &settings,
+            &mut heights,
+            Vector3::new(chunk_x, 0, chunk_z),
+        );
+        assert_eq!(chunks[0].blocks, again.blocks);
+    }
+    // the ground doesn't jump where one chunk ends and the next one begins
+    for z in -size..size {
+        let mut height = |x, z| heights.height(seed, &settings, x, z);
+        assert!((height(-1, z) - height(0, z)).abs() <= 3);
+        assert!((height(z, -1) - height(z, 0)).abs() <= 3);
+    }
+}
+
+#[test]
+fn caves_and_ores_only_depend_on_the_world_position() {
+    let seed = 77;
+    let settings = GeneratorSettings::default();
+    let mut heights = HeightCache::default();
+    let size = CHUNK_SIZE as i32;
+    let mut carved = 0;
+    let mut ores = 0;
+    for &(chunk_x, chunk_z) in &[(0, 0), (1, 0), (3, -2), (-4, 5)] {
+        let position = Vector3::new(chunk_x, 0, chunk_z);
+        let chunk = generate_chunk(seed, &settings, &mut heights, position);
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let world_x = chunk_x * size + x as i32;
+                let world_z = chunk_z * size + z as i32;
+                let column = Column::new(seed, &settings, &mut heights, world_x, world_z);
+                for y in 0..CHUNK_SIZE.min(column.height as usize) {
+                    let block = chunk.blocks[x][y][z];
+                    // holes in the ground are exactly where the carving says, whichever chunk it is
+                    let expected = column.is_carved(seed, y as i32);
+                    assert_eq!(block == Block::Empty || block == Block::Water, expected);
+                    if expected {
+                        carved += 1;
+                    }
+                    if let Some(ore) = ORES.iter().find(|ore| ore.block == block) {
+                        assert!(y as i32 >= ore.min_y && (y as i32) < ore.max_y);
+                        ores += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            chunk.blocks,
+            generate_chunk(seed, &settings, &mut heights, position).blocks
+        );
+    }
+    assert!(carved > 0);
+    assert!(ores > 0);
+}
+
+#[test]
+fn ore_veins_go_on_in_the_neighbouring_chunks() {
+    let seed = 77;
+    let settings = GeneratorSettings::default();
+    let mut heights = HeightCache::default();
+    let size = CHUNK_SIZE as i32;
+    assert!(ORES.iter().all(|ore| (ore.size as i32) < size));
+    let mut crossing = 0;
+    for &(chunk_x, chunk_z) in &[(0, 0), (1, 0), (0, 1)] {
+        let position = Vector3::new(chunk_x, 0, chunk_z);
+        let chunk = generate_chunk(seed, &settings, &mut heights, position);
+        for (i, ore) in ORES.iter().enumerate() {
+            for cell in [(-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+                let cell = position + Vector3::new(cell.0, 0, cell.1);
+                for block in ore_veins(seed, cell, i, ore) {
+                    let local = block - position * size;
+                    let inside = |c: i32| c >= 0 && c < size;
+                    if inside(local.x) && inside(local.y) && inside(local.z) {
+                        // the vein of the next chunk turned the stone here into ore too
+                        let (x, y, z) = (local.x as usize, local.y as usize, local.z as usize);
+                        assert_ne!(chunk.blocks[x][y][z], Block::Stone);
+                        crossing += 1;
+                    }
+                }
+            }
+        }
+    }
+    assert!(crossing > 0);
+}
+
+#[test]
+fn open_space_below_the_sea_level_is_water() {
+    let seed = 5;
+    let settings = GeneratorSettings::default();
+    let mut heights = HeightCache::default();
+    let size = CHUNK_SIZE as i32;
+    // the first wet column, beach and dry column carved below the sea level along the x axis
+    let (mut wet, mut beach, mut carved) = (None, None, None);
+    for x in 0..4000 {
+        let column = Column::new(seed, &settings, &mut heights, x, 0);
+        if column.height < settings.sea_level && column.biome != Biome::Mountains {
+            wet = wet.or(Some(column));
+        } else if !column.is_wet()
+            && column.is_beach()
+            && !column.is_carved(seed, column.height - 1)
+        {
+            beach = beach.or(Some(column));
+        } else if !column.is_wet() && carved.is_none() {
+            carved = (1..settings.sea_level)
+                .find(|y| column.is_carved(seed, *y))
+                .map(|y| (column, y));
+        }
+        if wet.is_some() && beach.is_some() && carved.is_some() {
+            break;
+        }
+    }
+    let (wet, beach, (carved, carved_y)) = (wet.unwrap(), beach.unwrap(), carved.unwrap());
+    let chunk = generate_chunk(
+        seed,
+        &settings,
+        &mut heights,
+        Vector3::new(carved.x.div_euclid(size), 0, 0),
+    );
+    assert_eq!(
+        chunk.blocks[carved.x.rem_euclid(size) as usize][carved_y as usize][0],
+        Block::Water
+    );
+    for column in [&wet, &beach].iter() {
+        let chunk_x = column.x.div_euclid(size);
+        let x = column.x.rem_euclid(size) as usize;
+        let chunk = generate_chunk(seed, &settings, &mut heights, Vector3::new(chunk_x, 0, 0));
+        for y in column.height..settings.sea_level {
+            assert_eq!(chunk.blocks[x][y as usize][0], Block::Water);
+        }
+        assert_eq!(column.surface(), Block::Sand);
+    }
+    let top = beach.height as usize - 1;
+    let chunk = generate_chunk(
+        seed,
+        &settings,
+        &mut heights,
+        Vector3::new(beach.x.div_euclid(size), 0, 0),
+    );
+    assert_eq!(
+        chunk.blocks[beach.x.rem_euclid(size) as usize][top][0],
+        Block::Sand
+    );
+}